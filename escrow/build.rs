@@ -0,0 +1,42 @@
+use std::{env, path::Path, process::Command};
+
+// builds tests/caller into OUT_DIR for the CPI test in src/tests/cpi.rs,
+// like target/deploy/escrow for the other mollusk tests it needs the Solana toolchain (cargo build-sbf)
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=tests/caller/src");
+    println!("cargo:rerun-if-changed=tests/caller/Cargo.toml");
+
+    // the sbf build of escrow has no tests to run, programs calling into escrow build it with no-entrypoint,
+    // and the caller's own build of escrow must not build the caller again
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("solana")
+        || env::var_os("CARGO_FEATURE_NO_ENTRYPOINT").is_some()
+        || env::var_os("ESCROW_CALLER_BUILD").is_some()
+    {
+        return;
+    }
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let manifest = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("tests/caller/Cargo.toml");
+
+    let mut build = Command::new("cargo");
+    build
+        .arg("build-sbf")
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .arg("--sbf-out-dir")
+        .arg(&out_dir)
+        .env("ESCROW_CALLER_BUILD", "1");
+
+    // the nested build picks its own toolchain, target and target dir, not the ones cargo set for this script
+    for (key, _) in env::vars_os() {
+        let key = key.to_string_lossy();
+        if key.starts_with("CARGO") || key.starts_with("RUSTC") || key.starts_with("RUSTUP_TOOLCHAIN") || key == "RUSTFLAGS" {
+            build.env_remove(key.as_ref());
+        }
+    }
+
+    if !matches!(build.status(), Ok(status) if status.success()) {
+        println!("cargo:warning=cargo build-sbf failed for tests/caller, the CPI test can't load the caller program");
+    }
+}
@@ -0,0 +1,174 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+pub use crate::processor::{EscrowArgs, EscrowInstruction, EscrowTerms, NftArgs};
+
+// helpers for programs that make, take and refund escrows through CPI,
+// `signer_seeds` are the caller's seeds for a PDA maker or taker, empty otherwise
+
+pub struct Make<'a, 'info> {
+    pub maker: &'a AccountInfo<'info>,
+    pub mint_a: &'a AccountInfo<'info>,
+    pub mint_b: &'a AccountInfo<'info>,
+    pub escrow: &'a AccountInfo<'info>,
+    pub maker_ta_a: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub remaining: &'a [AccountInfo<'info>], // the pair's index, basket legs, mint a's metadata, then the multisig and its signers
+}
+
+pub struct Take<'a, 'info> {
+    pub taker: &'a AccountInfo<'info>,
+    pub maker: &'a AccountInfo<'info>,
+    pub mint_a: &'a AccountInfo<'info>,
+    pub mint_b: &'a AccountInfo<'info>,
+    pub escrow: &'a AccountInfo<'info>,
+    pub maker_ta_b: &'a AccountInfo<'info>,
+    pub taker_ta_a: &'a AccountInfo<'info>,
+    pub taker_ta_b: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub remaining: &'a [AccountInfo<'info>], // the pair's index if listed, mint b's metadata for a collection bid, then (mint, taker_ta, vault) per basket leg
+}
+
+pub struct Refund<'a, 'info> {
+    pub maker: &'a AccountInfo<'info>,
+    pub mint_a: &'a AccountInfo<'info>,
+    pub escrow: &'a AccountInfo<'info>,
+    pub maker_ta_a: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub remaining: &'a [AccountInfo<'info>], // the pair's index if listed, basket legs, then the multisig and its signers
+}
+
+pub fn make<'info>(
+    escrow_program: &AccountInfo<'info>,
+    accounts: Make<'_, 'info>,
+    args: EscrowArgs,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_infos = vec![
+        accounts.maker.clone(),
+        accounts.mint_a.clone(),
+        accounts.mint_b.clone(),
+        accounts.escrow.clone(),
+        accounts.maker_ta_a.clone(),
+        accounts.vault.clone(),
+        accounts.token_program.clone(),
+        accounts.system_program.clone(),
+    ];
+    let mut account_metas = vec![
+        AccountMeta::new(*accounts.maker.key, true),
+        AccountMeta::new_readonly(*accounts.mint_a.key, false),
+        AccountMeta::new_readonly(*accounts.mint_b.key, false),
+        AccountMeta::new(*accounts.escrow.key, false),
+        AccountMeta::new(*accounts.maker_ta_a.key, false),
+        AccountMeta::new(*accounts.vault.key, false),
+        AccountMeta::new_readonly(*accounts.token_program.key, false),
+        AccountMeta::new_readonly(*accounts.system_program.key, false),
+    ];
+    extend(&mut account_infos, &mut account_metas, accounts.remaining);
+
+    invoke(escrow_program, account_infos, account_metas, &EscrowInstruction::Make(args), signer_seeds)
+}
+
+pub fn take<'info>(
+    escrow_program: &AccountInfo<'info>,
+    accounts: Take<'_, 'info>,
+    max_receive: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_infos = vec![
+        accounts.taker.clone(),
+        accounts.maker.clone(),
+        accounts.mint_a.clone(),
+        accounts.mint_b.clone(),
+        accounts.escrow.clone(),
+        accounts.maker_ta_b.clone(),
+        accounts.taker_ta_a.clone(),
+        accounts.taker_ta_b.clone(),
+        accounts.vault.clone(),
+        accounts.token_program.clone(),
+        accounts.system_program.clone(),
+    ];
+    let mut account_metas = vec![
+        AccountMeta::new(*accounts.taker.key, true),
+        AccountMeta::new(*accounts.maker.key, false),
+        AccountMeta::new_readonly(*accounts.mint_a.key, false),
+        AccountMeta::new_readonly(*accounts.mint_b.key, false),
+        AccountMeta::new(*accounts.escrow.key, false),
+        AccountMeta::new(*accounts.maker_ta_b.key, false),
+        AccountMeta::new(*accounts.taker_ta_a.key, false),
+        AccountMeta::new(*accounts.taker_ta_b.key, false),
+        AccountMeta::new(*accounts.vault.key, false),
+        AccountMeta::new_readonly(*accounts.token_program.key, false),
+        AccountMeta::new_readonly(*accounts.system_program.key, false),
+    ];
+    extend(&mut account_infos, &mut account_metas, accounts.remaining);
+
+    invoke(escrow_program, account_infos, account_metas, &EscrowInstruction::Take { max_receive }, signer_seeds)
+}
+
+pub fn refund<'info>(
+    escrow_program: &AccountInfo<'info>,
+    accounts: Refund<'_, 'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_infos = vec![
+        accounts.maker.clone(),
+        accounts.mint_a.clone(),
+        accounts.escrow.clone(),
+        accounts.maker_ta_a.clone(),
+        accounts.vault.clone(),
+        accounts.token_program.clone(),
+        accounts.system_program.clone(),
+    ];
+    let mut account_metas = vec![
+        AccountMeta::new(*accounts.maker.key, true),
+        AccountMeta::new_readonly(*accounts.mint_a.key, false),
+        AccountMeta::new(*accounts.escrow.key, false),
+        AccountMeta::new(*accounts.maker_ta_a.key, false),
+        AccountMeta::new(*accounts.vault.key, false),
+        AccountMeta::new_readonly(*accounts.token_program.key, false),
+        AccountMeta::new_readonly(*accounts.system_program.key, false),
+    ];
+    extend(&mut account_infos, &mut account_metas, accounts.remaining);
+
+    invoke(escrow_program, account_infos, account_metas, &EscrowInstruction::Refund, signer_seeds)
+}
+
+// remaining accounts keep the signer and writable flags they were passed in with
+fn extend<'info>(account_infos: &mut Vec<AccountInfo<'info>>, account_metas: &mut Vec<AccountMeta>, remaining: &[AccountInfo<'info>]) {
+    for account in remaining {
+        account_metas.push(AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        });
+        account_infos.push(account.clone());
+    }
+}
+
+fn invoke<'info>(
+    escrow_program: &AccountInfo<'info>,
+    mut account_infos: Vec<AccountInfo<'info>>,
+    account_metas: Vec<AccountMeta>,
+    instruction: &EscrowInstruction,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    assert!(crate::check_id(escrow_program.key));
+    account_infos.push(escrow_program.clone());
+
+    invoke_signed(
+        &Instruction::new_with_borsh(crate::id(), instruction, account_metas),
+        &account_infos,
+        signer_seeds,
+    )
+}
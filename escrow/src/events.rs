@@ -0,0 +1,209 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+
+// Events are logged as a single `Program data:` entry: 8 byte discriminator followed by the
+// borsh encoded event, so indexers can read what happened straight from the transaction logs.
+
+pub trait Event: BorshSerialize {
+    const DISCRIMINATOR: [u8; 8];
+
+    // the bytes logged by `emit`, what EscrowEvent::decode reads back
+    fn data(&self) -> Result<Vec<u8>, ProgramError> {
+        let mut data = Self::DISCRIMINATOR.to_vec();
+        self.serialize(&mut data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        Ok(data)
+    }
+
+    fn emit(&self) -> Result<(), ProgramError> {
+        sol_log_data(&[&self.data()?]);
+        Ok(())
+    }
+}
+
+// an additional deposit mint of a basket escrow
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct BasketLeg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowMade {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub authority: Pubkey, // the maker unless made with a multisig
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount: u64, // deposited into the vault
+    pub receive: u64,
+    pub basket: Vec<BasketLeg>,
+    pub nft: bool,
+    pub collection: Option<Pubkey>, // verified collection of the NFT, or the one a collection bid asks for
+}
+
+impl Event for EscrowMade {
+    const DISCRIMINATOR: [u8; 8] = *b"made\0\0\0\0";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowTaken {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub delegate: Option<Pubkey>, // signed instead of the taker through a token approval
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount: u64, // sent from the vault to the taker
+    pub receive: u64, // sent from the taker to the maker
+    pub basket: Vec<BasketLeg>,
+}
+
+impl Event for EscrowTaken {
+    const DISCRIMINATOR: [u8; 8] = *b"taken\0\0\0";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub amount: u64, // sent from the vault back to the maker
+    pub basket: Vec<BasketLeg>,
+}
+
+impl Event for EscrowRefunded {
+    const DISCRIMINATOR: [u8; 8] = *b"refunded";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowBid {
+    pub escrow: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub outbid: Option<Pubkey>, // previous highest bidder, refunded in the same instruction
+}
+
+impl Event for EscrowBid {
+    const DISCRIMINATOR: [u8; 8] = *b"bid\0\0\0\0\0";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowSettled {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub winner: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount: u64, // sent from the vault to the winner
+    pub receive: u64, // winning bid, sent to the maker
+}
+
+impl Event for EscrowSettled {
+    const DISCRIMINATOR: [u8; 8] = *b"settled\0";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowReleased {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint_a: Pubkey,
+    pub amount: u64, // sent from the vault to the beneficiary
+}
+
+impl Event for EscrowReleased {
+    const DISCRIMINATOR: [u8; 8] = *b"released";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowDisputed {
+    pub escrow: Pubkey,
+    pub disputer: Pubkey, // maker or beneficiary
+}
+
+impl Event for EscrowDisputed {
+    const DISCRIMINATOR: [u8; 8] = *b"disputed";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowResolved {
+    pub escrow: Pubkey,
+    pub arbiter: Pubkey,
+    pub recipient: Pubkey, // maker or beneficiary, as decided by the arbiter
+    pub mint_a: Pubkey,
+    pub amount: u64, // sent from the vault to the recipient
+}
+
+impl Event for EscrowResolved {
+    const DISCRIMINATOR: [u8; 8] = *b"resolved";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowVestingMade {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint_a: Pubkey,
+    pub amount: u64, // deposited into the vault
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+}
+
+impl Event for EscrowVestingMade {
+    const DISCRIMINATOR: [u8; 8] = *b"vesting\0";
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct EscrowClaimed {
+    pub escrow: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64, // sent from the vault to the beneficiary
+    pub claimed: u64, // claimed so far, including amount
+    pub total: u64,
+}
+
+impl Event for EscrowClaimed {
+    const DISCRIMINATOR: [u8; 8] = *b"claimed\0";
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EscrowEvent {
+    Made(EscrowMade),
+    Taken(EscrowTaken),
+    Refunded(EscrowRefunded),
+    Bid(EscrowBid),
+    Settled(EscrowSettled),
+    Released(EscrowReleased),
+    Disputed(EscrowDisputed),
+    Resolved(EscrowResolved),
+    VestingMade(EscrowVestingMade),
+    Claimed(EscrowClaimed),
+}
+
+impl EscrowEvent {
+    // decodes the raw bytes of a `Program data:` log entry (already base64 decoded)
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < 8 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (discriminator, mut payload) = data.split_at(8);
+
+        let event = match discriminator {
+            d if d == EscrowMade::DISCRIMINATOR => Self::Made(EscrowMade::deserialize(&mut payload)?),
+            d if d == EscrowTaken::DISCRIMINATOR => Self::Taken(EscrowTaken::deserialize(&mut payload)?),
+            d if d == EscrowRefunded::DISCRIMINATOR => Self::Refunded(EscrowRefunded::deserialize(&mut payload)?),
+            d if d == EscrowBid::DISCRIMINATOR => Self::Bid(EscrowBid::deserialize(&mut payload)?),
+            d if d == EscrowSettled::DISCRIMINATOR => Self::Settled(EscrowSettled::deserialize(&mut payload)?),
+            d if d == EscrowReleased::DISCRIMINATOR => Self::Released(EscrowReleased::deserialize(&mut payload)?),
+            d if d == EscrowDisputed::DISCRIMINATOR => Self::Disputed(EscrowDisputed::deserialize(&mut payload)?),
+            d if d == EscrowResolved::DISCRIMINATOR => Self::Resolved(EscrowResolved::deserialize(&mut payload)?),
+            d if d == EscrowVestingMade::DISCRIMINATOR => Self::VestingMade(EscrowVestingMade::deserialize(&mut payload)?),
+            d if d == EscrowClaimed::DISCRIMINATOR => Self::Claimed(EscrowClaimed::deserialize(&mut payload)?),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(event)
+    }
+}
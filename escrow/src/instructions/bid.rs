@@ -0,0 +1,165 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    rent::Rent,
+    system_instruction,
+    system_program,
+    sysvar::{clock::Clock, Sysvar},
+    pubkey::Pubkey,
+    program_error::ProgramError
+};
+
+use crate::{
+    events::{Event, EscrowBid},
+    state::{Bid, Escrow, EscrowKind},
+    utils::{check_token_account, check_vault, close_program_account, deposit, release_vault, withdraw_vault},
+};
+
+pub fn bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+
+    let [
+        bidder,
+        escrow,
+        mint_b,
+        bidder_ta_b,
+        bid,
+        bid_vault, // token account for mint b owned by the bid PDA, created by the client beforehand
+        token_program,
+        system_program,
+        outbid @ .. // (prev_bidder, prev_bidder_ta_b, prev_bid, prev_bid_vault) if someone else holds the highest bid
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that system program is correct
+    assert!(system_program::check_id(system_program.key));
+
+    // check that token program is correct
+    assert!(spl_token::check_id(token_program.key));
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    // check that bidder is signer
+    assert!(bidder.is_signer);
+
+    assert!(bidder.is_writable);
+
+    assert!(escrow.is_writable);
+    assert_eq!(escrow.owner, program_id);
+    let escrow_data = *Escrow::load(&escrow.try_borrow_data()?)?;
+    assert_eq!(EscrowKind::try_from(escrow_data.kind)?, EscrowKind::English);
+    assert_eq!(&escrow_data.mint_b, mint_b.key);
+
+    // bids are only accepted until the deadline
+    assert!(Clock::get()?.unix_timestamp < escrow_data.english.end_ts);
+
+    // the first bid has to meet the reserve, every later one has to beat the highest bid
+    assert!(amount >= escrow_data.receive && amount > escrow_data.english.highest_bid);
+
+    // the highest bidder raises its own bid in place, its bid PDA is already there
+    let raise = escrow_data.english.highest_bid > 0 && bidder.key == &escrow_data.english.highest_bidder;
+
+    // refund the previous highest bidder and close their bid
+    let outbid_bidder = if escrow_data.english.highest_bid > 0 && !raise {
+        let [prev_bidder, prev_bidder_ta_b, prev_bid, prev_bid_vault] = outbid else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        assert_eq!(prev_bidder.key, &escrow_data.english.highest_bidder);
+        check_token_account(prev_bidder_ta_b, mint_b.key, prev_bidder.key)?;
+
+        assert_eq!(prev_bid.owner, program_id);
+        let prev_bid_data = *Bid::load(&prev_bid.try_borrow_data()?)?;
+        let prev_bid_seeds: &[&[u8]] = &[b"bid", escrow.key.as_ref(), prev_bidder.key.as_ref(), &[prev_bid_data.bump]];
+        let expected_prev_bid = Pubkey::create_program_address(prev_bid_seeds, program_id)?;
+        assert_eq!(&expected_prev_bid, prev_bid.key);
+        check_vault(prev_bid_vault, mint_b.key, prev_bid.key)?;
+
+        release_vault(token_program, prev_bid, prev_bid_vault, mint_b, prev_bidder_ta_b, prev_bidder, prev_bid_seeds)?;
+        close_program_account(prev_bid, prev_bidder)?;
+
+        Some(*prev_bidder.key)
+    } else {
+        if !outbid.is_empty() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        None
+    };
+
+    assert!(bid.is_writable);
+    let bid_bump = if raise {
+        assert_eq!(bid.owner, program_id);
+        let bid_data = *Bid::load(&bid.try_borrow_data()?)?;
+        let bid_seeds: &[&[u8]] = &[b"bid", escrow.key.as_ref(), bidder.key.as_ref(), &[bid_data.bump]];
+        let expected_bid = Pubkey::create_program_address(bid_seeds, program_id)?;
+        assert_eq!(&expected_bid, bid.key);
+        check_vault(bid_vault, mint_b.key, bid.key)?;
+
+        // hand the previous bid back, the whole new amount is locked below
+        withdraw_vault(token_program, bid, bid_vault, mint_b, bidder_ta_b, bid_data.amount, bid_seeds)?;
+
+        bid_data.bump
+    } else {
+        assert!(bid.data_is_empty());
+        let (expected_bid, bid_bump) = Pubkey::find_program_address(&[b"bid", escrow.key.as_ref(), bidder.key.as_ref()], program_id);
+        assert_eq!(&expected_bid, bid.key);
+        let bid_seeds: &[&[u8]] = &[b"bid", escrow.key.as_ref(), bidder.key.as_ref(), &[bid_bump]];
+
+        check_vault(bid_vault, mint_b.key, bid.key)?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                bidder.key,
+                bid.key,
+                Rent::get()?.minimum_balance(Bid::LEN),
+                Bid::LEN as u64,
+                &crate::id()
+            ),
+            &[bidder.clone(), bid.clone(), system_program.clone()],
+            &[bid_seeds],
+        )?;
+
+        bid_bump
+    };
+
+    {
+        let mut bid_account_data = bid.try_borrow_mut_data()?;
+        let bid_data = bytemuck::try_from_bytes_mut::<Bid>(&mut bid_account_data)
+            .map_err(|_| ProgramError::AccountBorrowFailed)?;
+
+        *bid_data = Bid {
+            discriminator: Bid::DISCRIMINATOR,
+            version: Bid::VERSION,
+            bump: bid_bump,
+            _padding: [0; 6],
+            escrow: *escrow.key,
+            bidder: *bidder.key,
+            amount,
+        };
+    }
+
+    // lock the bid
+    deposit(token_program, mint_b, bidder_ta_b, bid_vault, bidder, amount)?;
+
+    {
+        let mut escrow_account_data = escrow.try_borrow_mut_data()?;
+        let escrow_data = Escrow::load_mut(&mut escrow_account_data)?;
+        escrow_data.english.highest_bidder = *bidder.key;
+        escrow_data.english.highest_bid = amount;
+    }
+
+    EscrowBid {
+        escrow: *escrow.key,
+        bidder: *bidder.key,
+        amount,
+        outbid: outbid_bidder,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    sysvar::{clock::Clock, Sysvar},
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    system_program,
+};
+
+use crate::{
+    events::{Event, EscrowClaimed},
+    state::{Escrow, EscrowKind},
+    utils::{check_token_account, check_vault, close_program_account, release_vault, withdraw_vault},
+};
+
+// the beneficiary withdraws whatever unlocked since the last claim,
+// the last claim closes the vault and the escrow back to the maker
+pub fn claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+
+    let [
+        beneficiary,
+        maker,
+        mint_a,
+        escrow,
+        vault,
+        beneficiary_ta_a,
+        token_program,
+        system_program
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that system program is correct
+    assert!(system_program::check_id(system_program.key));
+
+    // check that token program is correct
+    assert!(spl_token::check_id(token_program.key));
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    // check that beneficiary is signer
+    assert!(beneficiary.is_signer);
+
+    assert!(maker.is_writable && escrow.is_writable);
+
+    assert_eq!(escrow.owner, program_id);
+    let escrow_data = *Escrow::load(&escrow.try_borrow_data()?)?;
+    let escrow_seeds: &[&[u8]] = &[b"escrow", maker.key.as_ref(), &[escrow_data.bump]];
+    let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id)?; // save cu by using this instead of find_program_address
+    assert_eq!(&expected_escrow, escrow.key);
+
+    assert_eq!(EscrowKind::try_from(escrow_data.kind)?, EscrowKind::Vesting);
+    assert_eq!(beneficiary.key, &escrow_data.vesting.beneficiary);
+    assert_eq!(&escrow_data.mint_a, mint_a.key);
+
+    check_vault(vault, mint_a.key, escrow.key)?;
+    check_token_account(beneficiary_ta_a, mint_a.key, beneficiary.key)?;
+
+    let amount = escrow_data.vesting.claimable(Clock::get()?.unix_timestamp);
+    assert!(amount > 0);
+
+    let claimed = escrow_data.vesting.claimed.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    let total = escrow_data.vesting.total;
+
+    if claimed == total {
+        // fully vested, empty and close the vault (anything sent to it on top goes along) and close the escrow
+        release_vault(token_program, escrow, vault, mint_a, beneficiary_ta_a, maker, escrow_seeds)?;
+        close_program_account(escrow, maker)?;
+    } else {
+        // Transfer the unlocked part from vault to beneficiary_ta_a
+        withdraw_vault(token_program, escrow, vault, mint_a, beneficiary_ta_a, amount, escrow_seeds)?;
+
+        let mut escrow_account_data = escrow.try_borrow_mut_data()?;
+        Escrow::load_mut(&mut escrow_account_data)?.vesting.claimed = claimed;
+    }
+
+    EscrowClaimed {
+        escrow: *escrow.key,
+        beneficiary: *beneficiary.key,
+        amount,
+        claimed,
+        total,
+    }.emit()?;
+
+    Ok(())
+}
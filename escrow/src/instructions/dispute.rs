@@ -0,0 +1,56 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+};
+
+use crate::{
+    events::{Event, EscrowDisputed},
+    state::{Escrow, EscrowKind},
+    utils::check_authority,
+};
+
+// either party hands the escrow over to the arbiter
+pub fn dispute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+
+    let [
+        disputer, // maker or beneficiary
+        escrow,
+        authority_accounts @ .. // (multisig, signers...) when the maker disputes with a multisig authority
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    assert!(escrow.is_writable);
+    assert_eq!(escrow.owner, program_id);
+
+    let mut escrow_account_data = escrow.try_borrow_mut_data()?;
+    let escrow_data = Escrow::load_mut(&mut escrow_account_data)?;
+
+    assert_eq!(EscrowKind::try_from(escrow_data.kind)?, EscrowKind::Arbitrated);
+    if disputer.key == &escrow_data.arbitration.beneficiary {
+        // check that beneficiary is signer
+        assert!(disputer.is_signer);
+    } else {
+        // the maker side disputes with the same authority that releases
+        assert_eq!(disputer.key, &escrow_data.maker);
+        check_authority(program_id, escrow_data, disputer, authority_accounts)?;
+    }
+    assert_eq!(escrow_data.arbitration.disputed, 0);
+
+    escrow_data.arbitration.disputed = 1;
+
+    EscrowDisputed {
+        escrow: *escrow.key,
+        disputer: *disputer.key,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    rent::Rent,
+    sysvar::Sysvar,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+};
+
+use crate::state::Multisig;
+
+// sets up an M-of-N authority in an account the client created for this program,
+// the signers don't have to sign here, same as spl-token
+pub fn init_multisig(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    m: u8,
+) -> ProgramResult {
+
+    let [
+        multisig,
+        signers @ ..
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    assert!(multisig.is_writable);
+    assert_eq!(multisig.owner, program_id);
+    assert_eq!(multisig.data_len(), Multisig::LEN);
+    assert!(Rent::get()?.is_exempt(multisig.lamports(), Multisig::LEN));
+
+    let n = signers.len();
+    assert!((1..=Multisig::MAX_SIGNERS).contains(&n));
+    assert!(m >= 1 && m as usize <= n);
+
+    let mut multisig_account_data = multisig.try_borrow_mut_data()?;
+    let multisig_data = bytemuck::try_from_bytes_mut::<Multisig>(&mut multisig_account_data)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // only once, an initialized multisig keeps its signers
+    assert_eq!(multisig_data.discriminator, [0; 8]);
+
+    for (i, signer) in signers.iter().enumerate() {
+        // a duplicate would count twice towards n but only once towards m
+        assert!(!signers[..i].iter().any(|other| other.key == signer.key));
+        multisig_data.signers[i] = *signer.key;
+    }
+
+    multisig_data.discriminator = Multisig::DISCRIMINATOR;
+    multisig_data.version = Multisig::VERSION;
+    multisig_data.m = m;
+    multisig_data.n = n as u8;
+
+    Ok(())
+}
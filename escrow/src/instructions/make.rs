@@ -1,34 +1,39 @@
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
-    program::{invoke, invoke_signed},
-    program_pack::Pack,
+    program::invoke_signed,
     rent::Rent,
     system_instruction,
     system_program,
-    sysvar::Sysvar,
+    sysvar::{clock::Clock, Sysvar},
     pubkey::Pubkey,
-    program_error::ProgramError
+    program_error::ProgramError,
 };
-use spl_token::{instruction::transfer_checked, state::Mint};
+use bytemuck::Zeroable;
 
-use crate::{processor::EscrowArgs, state::Escrow};
+use crate::{
+    events::{BasketLeg, Event, EscrowMade},
+    processor::{EscrowArgs, EscrowTerms},
+    state::{Arbitration, DutchAuction, EnglishAuction, Escrow, EscrowKind, OrderBookEntry, Vesting},
+    utils::{check_collection, check_multisig, check_nft_mint, check_vault, deposit, order_book_insert, BASKET_ACCOUNTS},
+};
 
 pub fn make(
-    program_id: &Pubkey, 
-    accounts: &[AccountInfo], 
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
     args: EscrowArgs,
 ) -> ProgramResult {
 
     let [
-        maker, 
+        maker,
         mint_a,
         mint_b, // pubkey is enought, we don't need token info
         escrow,
         maker_ta_a,
         vault,
         token_program,
-        system_program
+        system_program,
+        remaining @ .. // the pair's index, then (mint, maker_ta, vault) per basket leg, then mint a's metadata for an NFT collection, then (multisig, signers...) with a multisig authority
      ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -38,7 +43,7 @@ pub fn make(
     assert!(system_program::check_id(system_program.key));
 
     // check that token program is correct - not needed is already performed?
-    // assert!(spl_token::check_id(token_program.key)); 
+    // assert!(spl_token::check_id(token_program.key));
 
     // check that own program id is correct
     assert!(crate::check_id(program_id));
@@ -51,21 +56,136 @@ pub fn make(
     // assert_eq!(mint_a.owner, token_program.key); no neeed
     // assert_eq!(mint_b.owner, token_program.key); no neeed
     // assert_eq!(mint_ta_a.owner, token_program.key);
-    assert_eq!(vault.owner, system_program.key); // because vault was not created yet?
 
     // assert!(vault.is_writable); // berg didn't check this ?
 
-    let mint_unpacked = Mint::unpack(&mint_a.try_borrow_data()?)?;
+    let mut dutch = DutchAuction::zeroed();
+    let mut english = EnglishAuction::zeroed();
+    let mut arbitration = Arbitration::zeroed();
+    let mut collection = Pubkey::default();
+    let kind = match args.terms {
+        EscrowTerms::Fixed => EscrowKind::Fixed,
+        EscrowTerms::Dutch(auction) => {
+            assert!(auction.is_valid() && auction.start_receive == args.receive);
+            dutch = auction;
+            EscrowKind::Dutch
+        }
+        EscrowTerms::English { end_ts } => {
+            assert!(end_ts > Clock::get()?.unix_timestamp);
+            // settle only swaps the main vault
+            assert!(args.basket_amounts.is_empty());
+            english.end_ts = end_ts;
+            EscrowKind::English
+        }
+        EscrowTerms::Arbitrated { beneficiary, arbiter } => {
+            // release and resolve only pay out the main vault
+            assert!(args.basket_amounts.is_empty());
+            // the arbiter decides between the two sides, it can't be one of them
+            assert!(arbiter != *maker.key && arbiter != beneficiary);
+            arbitration.beneficiary = beneficiary;
+            arbitration.arbiter = arbiter;
+            EscrowKind::Arbitrated
+        }
+        EscrowTerms::CollectionBid { collection: bid_collection } => {
+            // the maker deposits the price and asks for one NFT, whichever the taker brings
+            assert_eq!(mint_b.key, &Pubkey::default());
+            assert_eq!(args.receive, 1);
+            assert!(args.nft.is_none());
+            collection = bid_collection;
+            EscrowKind::CollectionBid
+        }
+    };
+
+    // only single mint offers at a listed price fit in the index
+    let (index, remaining) = match (args.index, remaining) {
+        (true, [index, remaining @ ..]) => {
+            assert!(matches!(kind, EscrowKind::Fixed | EscrowKind::Dutch) && args.basket_amounts.is_empty());
+            // the index ranks offers by receive per amount, which zero on either side breaks
+            assert!(args.amount > 0 && args.receive > 0);
+            (Some(index), remaining)
+        }
+        (true, []) => return Err(ProgramError::NotEnoughAccountKeys),
+        (false, _) => (None, remaining),
+    };
+
+    let extra_mints = args.basket_amounts.len();
+    assert!(extra_mints <= Escrow::MAX_EXTRA_MINTS);
+    if remaining.len() < extra_mints * BASKET_ACCOUNTS {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (basket, remaining) = remaining.split_at(extra_mints * BASKET_ACCOUNTS);
+
+    // an NFT offer is exactly one token of a mint that can only ever have one
+    let authority_accounts = match &args.nft {
+        Some(nft) => {
+            check_nft_mint(mint_a)?;
+            assert_eq!(args.amount, 1);
+
+            match nft.collection {
+                Some(nft_collection) => {
+                    let [metadata, authority_accounts @ ..] = remaining else {
+                        return Err(ProgramError::NotEnoughAccountKeys);
+                    };
+                    check_collection(mint_a, metadata, &nft_collection)?;
+                    collection = nft_collection;
+                    authority_accounts
+                }
+                None => remaining,
+            }
+        }
+        None => remaining,
+    };
+
+    // a multisig authority has to approve the offer it will control
+    let authority = match args.authority {
+        Some(authority) => {
+            let [multisig, signers @ ..] = authority_accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            assert_eq!(multisig.key, &authority);
+            check_multisig(program_id, multisig, signers)?;
+            authority
+        }
+        None => {
+            if !authority_accounts.is_empty() {
+                return Err(ProgramError::InvalidArgument);
+            }
+            *maker.key
+        }
+    };
 
     assert!(escrow.is_writable && escrow.data_is_empty());
-    let escrow_seeds = &[b"escrow", maker.key.as_ref(), &[args.escrow_bump]];
+
+    // derive the canonical bump once here, take and refund only re-derive with the stored bump
+    let (expected_escrow, escrow_bump) = Pubkey::find_program_address(&[b"escrow", maker.key.as_ref()], program_id);
+    assert_eq!(&expected_escrow, escrow.key);
+    assert_eq!(args.escrow_bump, escrow_bump);
+    let escrow_seeds = &[b"escrow", maker.key.as_ref(), &[escrow_bump]];
+
+    // vaults are token accounts owned by the escrow PDA, created by the client beforehand
+    check_vault(vault, mint_a.key, escrow.key)?;
+
+    let mut basket_mints = Vec::with_capacity(extra_mints);
+    for leg in basket.chunks_exact(BASKET_ACCOUNTS) {
+        let [mint, _maker_ta, basket_vault] = leg else {
+            unreachable!()
+        };
+
+        // every mint can only be in the basket once
+        assert!(mint.key != mint_a.key && !basket_mints.contains(mint.key));
+        check_vault(basket_vault, mint.key, escrow.key)?;
+
+        basket_mints.push(*mint.key);
+    }
+
+    let space = Escrow::space(extra_mints);
 
     invoke_signed(
         &system_instruction::create_account(
-            maker.key, //from_pubkey: 
+            maker.key, //from_pubkey:
             escrow.key, //to_pubkey,
-            Rent::get()?.minimum_balance(Escrow::LEN), //lamports,
-            Escrow::LEN as u64, //space,
+            Rent::get()?.minimum_balance(space), //lamports,
+            space as u64, //space,
             &crate::id()//program_id
         ),
         accounts, // account_infos????
@@ -73,36 +193,75 @@ pub fn make(
     )?;
 
     let new_escrow = Escrow{
+        discriminator: Escrow::DISCRIMINATOR,
+        version: Escrow::VERSION,
+        bump: escrow_bump,
+        extra_mints: extra_mints as u8,
+        kind: kind as u8,
+        nft: args.nft.is_some() as u8,
+        indexed: args.index as u8,
+        _padding: [0; 2],
         maker: *maker.key,
+        authority,
         mint_a: *mint_a.key,
         mint_b: *mint_b.key,
+        collection,
         receive: args.receive,
-        bump: args.escrow_bump as u64,
+        dutch,
+        english,
+        arbitration,
+        vesting: Vesting::zeroed(),
     };
 
-    let mut escrow_data = *bytemuck::try_from_bytes_mut::<Escrow>(*escrow.data.borrow_mut())
-        .map_err(|_| ProgramError::AccountBorrowFailed)?;
+    // write through the borrow, copying the struct out would leave the account zeroed
+    {
+        let mut escrow_account_data = escrow.try_borrow_mut_data()?;
+        let (header, tail) = escrow_account_data.split_at_mut(Escrow::LEN);
+
+        let escrow_data = bytemuck::try_from_bytes_mut::<Escrow>(header)
+            .map_err(|_| ProgramError::AccountBorrowFailed)?;
+        escrow_data.clone_from(&new_escrow);
 
-    escrow_data.clone_from(&new_escrow);
+        tail.copy_from_slice(bytemuck::cast_slice(&basket_mints));
+    }
 
     // Transfer to vault
-    invoke(
-        &transfer_checked(
-           token_program.key,
-           maker_ta_a.key,
-           mint_a.key,
-           vault.key,
-           maker.key,
-           &[],
-           args.amount,
-           mint_unpacked.decimals,
-        )?,
-        &[
-            maker.clone(),
-            maker_ta_a.clone(),
-            vault.clone(),
-        ]
-    )?;
+    deposit(token_program, mint_a, maker_ta_a, vault, maker, args.amount)?;
+
+    if let Some(index) = index {
+        order_book_insert(program_id, index, maker, system_program, mint_a.key, mint_b.key, OrderBookEntry {
+            escrow: *escrow.key,
+            amount: args.amount,
+            receive: args.receive,
+        })?;
+    }
+
+    let mut basket_legs = Vec::with_capacity(extra_mints);
+    for (leg, amount) in basket.chunks_exact(BASKET_ACCOUNTS).zip(args.basket_amounts) {
+        let [mint, maker_ta, basket_vault] = leg else {
+            unreachable!()
+        };
+
+        deposit(token_program, mint, maker_ta, basket_vault, maker, amount)?;
+
+        basket_legs.push(BasketLeg {
+            mint: *mint.key,
+            amount,
+        });
+    }
+
+    EscrowMade {
+        escrow: *escrow.key,
+        maker: *maker.key,
+        authority,
+        mint_a: *mint_a.key,
+        mint_b: *mint_b.key,
+        amount: args.amount,
+        receive: args.receive,
+        basket: basket_legs,
+        nft: args.nft.is_some(),
+        collection: (collection != Pubkey::default()).then_some(collection),
+    }.emit()?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,131 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    rent::Rent,
+    system_instruction,
+    system_program,
+    sysvar::Sysvar,
+    pubkey::Pubkey,
+    program_error::ProgramError
+};
+use bytemuck::Zeroable;
+
+use crate::{
+    events::{Event, EscrowVestingMade},
+    processor::VestingArgs,
+    state::{Arbitration, DutchAuction, EnglishAuction, Escrow, EscrowKind, Vesting},
+    utils::{check_vault, deposit},
+};
+
+// locks mint a in the vault for a beneficiary who claims it over time, see claim
+pub fn make_vesting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: VestingArgs,
+) -> ProgramResult {
+
+    let [
+        maker,
+        mint_a,
+        escrow,
+        maker_ta_a,
+        vault,
+        token_program,
+        system_program
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that system program is correct
+    assert!(system_program::check_id(system_program.key));
+
+    // check that token program is correct
+    assert!(spl_token::check_id(token_program.key));
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    // check that maker is signer
+    assert!(maker.is_signer);
+
+    assert!(maker.is_writable);
+
+    let vesting = Vesting {
+        beneficiary: args.beneficiary,
+        total: args.amount,
+        claimed: 0,
+        start_ts: args.start_ts,
+        cliff_ts: args.cliff_ts,
+        end_ts: args.end_ts,
+    };
+    assert!(args.amount > 0 && vesting.is_valid());
+
+    assert!(escrow.is_writable && escrow.data_is_empty());
+
+    // derive the canonical bump once here, claim only re-derives with the stored bump
+    let (expected_escrow, escrow_bump) = Pubkey::find_program_address(&[b"escrow", maker.key.as_ref()], program_id);
+    assert_eq!(&expected_escrow, escrow.key);
+    assert_eq!(args.escrow_bump, escrow_bump);
+    let escrow_seeds = &[b"escrow", maker.key.as_ref(), &[escrow_bump]];
+
+    // vaults are token accounts owned by the escrow PDA, created by the client beforehand
+    check_vault(vault, mint_a.key, escrow.key)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            maker.key,
+            escrow.key,
+            Rent::get()?.minimum_balance(Escrow::LEN),
+            Escrow::LEN as u64,
+            &crate::id()
+        ),
+        &[maker.clone(), escrow.clone(), system_program.clone()],
+        &[escrow_seeds],
+    )?;
+
+    // nothing is paid back in mint b, mint_b and receive stay zeroed
+    let new_escrow = Escrow {
+        discriminator: Escrow::DISCRIMINATOR,
+        version: Escrow::VERSION,
+        bump: escrow_bump,
+        extra_mints: 0,
+        kind: EscrowKind::Vesting as u8,
+        nft: 0,
+        indexed: 0,
+        _padding: [0; 2],
+        maker: *maker.key,
+        authority: *maker.key,
+        mint_a: *mint_a.key,
+        mint_b: Pubkey::default(),
+        collection: Pubkey::default(),
+        receive: 0,
+        dutch: DutchAuction::zeroed(),
+        english: EnglishAuction::zeroed(),
+        arbitration: Arbitration::zeroed(),
+        vesting,
+    };
+
+    {
+        let mut escrow_account_data = escrow.try_borrow_mut_data()?;
+        let escrow_data = bytemuck::try_from_bytes_mut::<Escrow>(&mut escrow_account_data)
+            .map_err(|_| ProgramError::AccountBorrowFailed)?;
+        escrow_data.clone_from(&new_escrow);
+    }
+
+    // Transfer to vault
+    deposit(token_program, mint_a, maker_ta_a, vault, maker, args.amount)?;
+
+    EscrowVestingMade {
+        escrow: *escrow.key,
+        maker: *maker.key,
+        beneficiary: args.beneficiary,
+        mint_a: *mint_a.key,
+        amount: args.amount,
+        start_ts: args.start_ts,
+        cliff_ts: args.cliff_ts,
+        end_ts: args.end_ts,
+    }.emit()?;
+
+    Ok(())
+}
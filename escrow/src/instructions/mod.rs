@@ -1,7 +1,25 @@
 pub mod make;
 pub mod take;
 pub mod refund;
+pub mod take_many;
+pub mod bid;
+pub mod settle;
+pub mod release;
+pub mod dispute;
+pub mod resolve;
+pub mod make_vesting;
+pub mod claim;
+pub mod init_multisig;
 
 pub use make::*;
 pub use take::*;
 pub use refund::*;
+pub use take_many::*;
+pub use bid::*;
+pub use settle::*;
+pub use release::*;
+pub use dispute::*;
+pub use resolve::*;
+pub use make_vesting::*;
+pub use claim::*;
+pub use init_multisig::*;
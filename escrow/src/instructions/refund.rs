@@ -1,15 +1,16 @@
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
-    program::invoke_signed,
     pubkey::Pubkey,
     program_error::ProgramError,
-    program_pack::Pack,
     system_program,
 };
-use spl_token::{instruction::{close_account, transfer_checked}, state::Mint};
 
-use crate::state::Escrow;
+use crate::{
+    events::{BasketLeg, Event, EscrowRefunded},
+    state::{Escrow, EscrowKind},
+    utils::{check_authority, check_vault, close_program_account, order_book_remove, release_vault, BASKET_ACCOUNTS},
+};
 
 pub fn refund(
     program_id: &Pubkey, 
@@ -23,7 +24,8 @@ pub fn refund(
         maker_ta_a,
         vault,
         token_program,
-        system_program
+        system_program,
+        remaining @ .. // the pair's index if listed, then (mint, maker_ta, vault) per basket leg, then (multisig, signers...) with a multisig authority
      ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -38,9 +40,6 @@ pub fn refund(
     // check that own program id is correct
     assert!(crate::check_id(program_id));
 
-    // check that maker is signer
-    assert!(maker.is_signer);
-
     assert!(maker.is_writable);
 
     // assert_eq!(mint_a.owner, token_program.key);
@@ -49,55 +48,75 @@ pub fn refund(
 
     // assert!(vault.is_writable); // berg didn't check this ?
 
-    let mint_a_decimals = Mint::unpack(&mint_a.try_borrow_data()?)?.decimals;
-
-    // let escrow_seeds = &[b"escrow", maker.key.as_ref(), &[args.escrow_bump]];
-    // let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id).unwrap();
-    // assert_eq!(&expected_escrow, escrow.key);
-
-    // assert!(validate_escrow_seeds(escrow_seeds, escrow.key));
-    // let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id).unwrap(); // save cu by using this instead of find_program_address
-    // assert_eq!(&expected_escrow, escrow.key);
-    // assert_eq!(escrow.owner, program_id);
-
-    let escrow_data = *bytemuck::try_from_bytes_mut::<Escrow>(*escrow.data.borrow_mut()).map_err(|_| ProgramError::AccountBorrowFailed)?;
-    let escrow_seeds = &[b"escrow", maker.key.as_ref(), &[escrow_data.bump as u8]];
-
-    // Transfer A from vault to taker_ta_a
-    invoke_signed(
-        &transfer_checked(
-           token_program.key,
-           vault.key,
-           mint_a.key,
-           maker_ta_a.key,
-           escrow.key,
-           &[],
-           escrow_data.receive,
-           mint_a_decimals,
-        )?,
-        accounts,
-        &[escrow_seeds]
-    )?;
+    assert_eq!(escrow.owner, program_id);
+    let escrow_data = *Escrow::load(&escrow.try_borrow_data()?)?;
+    let escrow_seeds: &[&[u8]] = &[b"escrow", maker.key.as_ref(), &[escrow_data.bump]];
+    let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id)?; // save cu by using this instead of find_program_address
+    assert_eq!(&expected_escrow, escrow.key);
+
+    assert_eq!(&escrow_data.mint_a, mint_a.key);
+
+    match EscrowKind::try_from(escrow_data.kind)? {
+        // once there is a bid the auction has to be settled, it can't be pulled anymore
+        EscrowKind::English => assert_eq!(escrow_data.english.highest_bid, 0),
+        // the beneficiary may already have delivered, only the arbiter can send the funds back
+        EscrowKind::Arbitrated => return Err(ProgramError::InvalidAccountData),
+        // vested tokens belong to the beneficiary
+        EscrowKind::Vesting => return Err(ProgramError::InvalidAccountData),
+        EscrowKind::Fixed | EscrowKind::Dutch | EscrowKind::CollectionBid => {}
+    }
+    check_vault(vault, mint_a.key, escrow.key)?;
+
+    let (index, remaining) = match (escrow_data.indexed != 0, remaining) {
+        (true, [index, remaining @ ..]) => (Some(index), remaining),
+        (true, []) => return Err(ProgramError::NotEnoughAccountKeys),
+        (false, _) => (None, remaining),
+    };
+
+    // every basket leg has to be passed in, in the order make stored them
+    let basket_mints = Escrow::basket(&escrow.try_borrow_data()?)?.to_vec();
+    if remaining.len() < basket_mints.len() * BASKET_ACCOUNTS {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (basket, authority_accounts) = remaining.split_at(basket_mints.len() * BASKET_ACCOUNTS);
+
+    // the maker, or m of n signers of its multisig
+    check_authority(program_id, &escrow_data, maker, authority_accounts)?;
+
+    // refund everything in the vault, `receive` is denominated in mint b
+    // Transfer A from vault to maker_ta_a and close the vault
+    let a_amount = release_vault(token_program, escrow, vault, mint_a, maker_ta_a, maker, escrow_seeds)?;
+
+    let mut basket_legs = Vec::with_capacity(basket_mints.len());
+    for (leg, basket_mint) in basket.chunks_exact(BASKET_ACCOUNTS).zip(basket_mints.iter()) {
+        let [mint, maker_ta, basket_vault] = leg else {
+            unreachable!()
+        };
+        assert_eq!(mint.key, basket_mint);
+        check_vault(basket_vault, mint.key, escrow.key)?;
+
+        let amount = release_vault(token_program, escrow, basket_vault, mint, maker_ta, maker, escrow_seeds)?;
+
+        basket_legs.push(BasketLeg {
+            mint: *mint.key,
+            amount,
+        });
+    }
+
+    if let Some(index) = index {
+        order_book_remove(program_id, index, &escrow_data, escrow.key, maker)?;
+    }
 
     // close escrow
-    let mut escrow_data = escrow.data.borrow_mut();
-    escrow_data.fill(0);
-    let maker_orig_lamports = maker.lamports();
-    **maker.lamports.borrow_mut() = maker_orig_lamports.checked_add(escrow.lamports()).ok_or(ProgramError::ArithmeticOverflow)?;
-    **escrow.lamports.borrow_mut() = 0;
-
-    // close vault
-    invoke_signed(
-        &close_account(
-            token_program.key,
-            vault.key,
-            maker.key,
-            escrow.key,
-            &[],
-        )?,
-        accounts,
-        &[escrow_seeds]
-    )?;
+    close_program_account(escrow, maker)?;
+
+    EscrowRefunded {
+        escrow: *escrow.key,
+        maker: *maker.key,
+        mint_a: *mint_a.key,
+        amount: a_amount,
+        basket: basket_legs,
+    }.emit()?;
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    system_program,
+};
+
+use crate::{
+    events::{Event, EscrowReleased},
+    state::{Escrow, EscrowKind},
+    utils::{check_authority, check_token_account, check_vault, close_program_account, release_vault},
+};
+
+// the maker approves the delivery and pays the vault out to the beneficiary
+pub fn release(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+
+    let [
+        maker,
+        beneficiary,
+        mint_a,
+        escrow,
+        vault,
+        beneficiary_ta_a,
+        token_program,
+        system_program,
+        authority_accounts @ .. // (multisig, signers...) with a multisig authority
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that system program is correct
+    assert!(system_program::check_id(system_program.key));
+
+    // check that token program is correct
+    assert!(spl_token::check_id(token_program.key));
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    assert!(maker.is_writable);
+
+    assert_eq!(escrow.owner, program_id);
+    let escrow_data = *Escrow::load(&escrow.try_borrow_data()?)?;
+    let escrow_seeds: &[&[u8]] = &[b"escrow", maker.key.as_ref(), &[escrow_data.bump]];
+    let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id)?; // save cu by using this instead of find_program_address
+    assert_eq!(&expected_escrow, escrow.key);
+
+    // releasing is always up to the maker (or its multisig), also while disputed
+    check_authority(program_id, &escrow_data, maker, authority_accounts)?;
+    assert_eq!(EscrowKind::try_from(escrow_data.kind)?, EscrowKind::Arbitrated);
+    assert_eq!(beneficiary.key, &escrow_data.arbitration.beneficiary);
+    assert_eq!(&escrow_data.mint_a, mint_a.key);
+
+    check_vault(vault, mint_a.key, escrow.key)?;
+    check_token_account(beneficiary_ta_a, mint_a.key, beneficiary.key)?;
+
+    // Transfer A from vault to beneficiary_ta_a and close the vault
+    let amount = release_vault(token_program, escrow, vault, mint_a, beneficiary_ta_a, maker, escrow_seeds)?;
+
+    // close escrow
+    close_program_account(escrow, maker)?;
+
+    EscrowReleased {
+        escrow: *escrow.key,
+        maker: *maker.key,
+        beneficiary: *beneficiary.key,
+        mint_a: *mint_a.key,
+        amount,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    system_program,
+};
+
+use crate::{
+    events::{Event, EscrowResolved},
+    processor::ArbiterDecision,
+    state::{Escrow, EscrowKind},
+    utils::{check_token_account, check_vault, close_program_account, release_vault},
+};
+
+// the arbiter pays a disputed escrow out to whichever side it decided for,
+// rent of the vault and the escrow always goes back to the maker
+pub fn resolve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    decision: ArbiterDecision,
+) -> ProgramResult {
+
+    let [
+        arbiter,
+        maker,
+        mint_a,
+        escrow,
+        vault,
+        recipient_ta_a, // token account of the maker or the beneficiary, depending on the decision
+        token_program,
+        system_program
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that system program is correct
+    assert!(system_program::check_id(system_program.key));
+
+    // check that token program is correct
+    assert!(spl_token::check_id(token_program.key));
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    // check that arbiter is signer
+    assert!(arbiter.is_signer);
+
+    assert!(maker.is_writable);
+
+    assert_eq!(escrow.owner, program_id);
+    let escrow_data = *Escrow::load(&escrow.try_borrow_data()?)?;
+    let escrow_seeds: &[&[u8]] = &[b"escrow", maker.key.as_ref(), &[escrow_data.bump]];
+    let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id)?; // save cu by using this instead of find_program_address
+    assert_eq!(&expected_escrow, escrow.key);
+
+    assert_eq!(EscrowKind::try_from(escrow_data.kind)?, EscrowKind::Arbitrated);
+    assert_eq!(arbiter.key, &escrow_data.arbitration.arbiter);
+    assert_eq!(escrow_data.arbitration.disputed, 1);
+    assert_eq!(&escrow_data.mint_a, mint_a.key);
+
+    let recipient = match decision {
+        ArbiterDecision::Maker => escrow_data.maker,
+        ArbiterDecision::Beneficiary => escrow_data.arbitration.beneficiary,
+    };
+
+    check_vault(vault, mint_a.key, escrow.key)?;
+    check_token_account(recipient_ta_a, mint_a.key, &recipient)?;
+
+    // Transfer A from vault to the recipient and close the vault
+    let amount = release_vault(token_program, escrow, vault, mint_a, recipient_ta_a, maker, escrow_seeds)?;
+
+    // close escrow
+    close_program_account(escrow, maker)?;
+
+    EscrowResolved {
+        escrow: *escrow.key,
+        arbiter: *arbiter.key,
+        recipient,
+        mint_a: *mint_a.key,
+        amount,
+    }.emit()?;
+
+    Ok(())
+}
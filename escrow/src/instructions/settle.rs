@@ -0,0 +1,98 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    sysvar::{clock::Clock, Sysvar},
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    system_program,
+};
+
+use crate::{
+    events::{Event, EscrowSettled},
+    state::{Bid, Escrow, EscrowKind},
+    utils::{check_token_account, check_vault, close_program_account, release_vault},
+};
+
+// swaps the maker's vault with the winning bid once the auction is over,
+// every destination is fixed by the escrow so anyone can crank it
+pub fn settle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+
+    let [
+        maker,
+        winner,
+        mint_a,
+        mint_b,
+        escrow,
+        vault,
+        winner_ta_a,
+        bid,
+        bid_vault,
+        maker_ta_b,
+        token_program,
+        system_program
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // check that system program is correct
+    assert!(system_program::check_id(system_program.key));
+
+    // check that token program is correct
+    assert!(spl_token::check_id(token_program.key));
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    assert!(maker.is_writable && winner.is_writable);
+
+    assert_eq!(escrow.owner, program_id);
+    let escrow_data = *Escrow::load(&escrow.try_borrow_data()?)?;
+    let escrow_seeds: &[&[u8]] = &[b"escrow", maker.key.as_ref(), &[escrow_data.bump]];
+    let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id)?; // save cu by using this instead of find_program_address
+    assert_eq!(&expected_escrow, escrow.key);
+
+    assert_eq!(EscrowKind::try_from(escrow_data.kind)?, EscrowKind::English);
+    assert_eq!(&escrow_data.mint_a, mint_a.key);
+    assert_eq!(&escrow_data.mint_b, mint_b.key);
+
+    // only after the deadline and only with a bid, without one the maker refunds
+    assert!(Clock::get()?.unix_timestamp >= escrow_data.english.end_ts);
+    assert!(escrow_data.english.highest_bid > 0);
+    assert_eq!(winner.key, &escrow_data.english.highest_bidder);
+
+    assert_eq!(bid.owner, program_id);
+    let bid_data = *Bid::load(&bid.try_borrow_data()?)?;
+    let bid_seeds: &[&[u8]] = &[b"bid", escrow.key.as_ref(), winner.key.as_ref(), &[bid_data.bump]];
+    let expected_bid = Pubkey::create_program_address(bid_seeds, program_id)?;
+    assert_eq!(&expected_bid, bid.key);
+
+    check_vault(vault, mint_a.key, escrow.key)?;
+    check_vault(bid_vault, mint_b.key, bid.key)?;
+    check_token_account(winner_ta_a, mint_a.key, winner.key)?;
+    check_token_account(maker_ta_b, mint_b.key, maker.key)?;
+
+    // Transfer A from vault to the winner, vault rent goes back to the maker
+    let a_amount = release_vault(token_program, escrow, vault, mint_a, winner_ta_a, maker, escrow_seeds)?;
+
+    // Transfer the winning bid to the maker, bid vault rent goes back to the winner
+    let b_amount = release_vault(token_program, bid, bid_vault, mint_b, maker_ta_b, winner, bid_seeds)?;
+
+    // close bid and escrow
+    close_program_account(bid, winner)?;
+    close_program_account(escrow, maker)?;
+
+    EscrowSettled {
+        escrow: *escrow.key,
+        maker: *maker.key,
+        winner: *winner.key,
+        mint_a: *mint_a.key,
+        mint_b: *mint_b.key,
+        amount: a_amount,
+        receive: b_amount,
+    }.emit()?;
+
+    Ok(())
+}
@@ -1,23 +1,250 @@
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
-    program::{invoke, invoke_signed},
+    program::invoke,
     program_pack::Pack,
     pubkey::Pubkey,
     program_error::ProgramError,
-    system_program
+    system_program,
+    sysvar::{clock::Clock, Sysvar},
 };
-use spl_token::{instruction::{close_account, transfer_checked}, state::Mint};
+use spl_token::{instruction::transfer_checked, state::{Account, Mint}};
 
-use crate::state::Escrow;
+use crate::{
+    events::{BasketLeg, Event, EscrowTaken},
+    state::{Escrow, EscrowKind},
+    utils::{check_collection, check_nft_mint, check_token_account, check_vault, close_program_account, order_book_remove, release_vault, BASKET_ACCOUNTS},
+};
+
+// taker side of a fill, shared by every offer in take and take_many
+pub struct Taker<'a, 'info> {
+    pub taker: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>, // signs the mint b transfer, the taker or its delegate on taker_ta_b
+    pub mint_a: &'a AccountInfo<'info>,
+    pub mint_b: &'a AccountInfo<'info>,
+    pub taker_ta_a: &'a AccountInfo<'info>,
+    pub taker_ta_b: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub mint_b_decimals: u8,
+}
+
+// maker side of a fill, one per escrow
+pub struct Offer<'a, 'info> {
+    pub escrow: &'a AccountInfo<'info>,
+    pub maker: &'a AccountInfo<'info>,
+    pub maker_ta_b: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub index: Option<&'a AccountInfo<'info>>, // the pair's index, required for listed offers
+    pub metadata: Option<&'a AccountInfo<'info>>, // metadata of the NFT the taker sells into a collection bid
+    pub basket: &'a [AccountInfo<'info>], // (mint, taker_ta, vault) per basket leg
+}
+
+impl<'a, 'info> Taker<'a, 'info> {
+    // with a delegate the taker doesn't sign, the delegate pays out of taker_ta_b
+    // through its token approval and mint a still goes to the taker
+    pub fn new(
+        delegate: Option<&'a AccountInfo<'info>>,
+        taker: &'a AccountInfo<'info>,
+        mint_a: &'a AccountInfo<'info>,
+        mint_b: &'a AccountInfo<'info>,
+        taker_ta_a: &'a AccountInfo<'info>,
+        taker_ta_b: &'a AccountInfo<'info>,
+        token_program: &'a AccountInfo<'info>,
+    ) -> Result<Self, ProgramError> {
+        // check that token program is correct
+        assert!(spl_token::check_id(token_program.key));
+
+        let authority = match delegate {
+            Some(delegate) => {
+                // check that delegate is signer
+                assert!(delegate.is_signer);
+
+                // both sides of the swap have to be the taker's own token accounts
+                check_token_account(taker_ta_a, mint_a.key, taker.key)?;
+                check_token_account(taker_ta_b, mint_b.key, taker.key)?;
+                assert_eq!(Account::unpack(&taker_ta_b.try_borrow_data()?)?.delegate, Some(*delegate.key).into());
+
+                delegate
+            }
+            None => {
+                // check that taker is signer
+                assert!(taker.is_signer);
+
+                assert!(taker.is_writable);
+
+                taker
+            }
+        };
+
+        let mint_b_decimals = Mint::unpack(&mint_b.try_borrow_data()?)?.decimals;
+
+        Ok(Self {
+            taker,
+            authority,
+            mint_a,
+            mint_b,
+            taker_ta_a,
+            taker_ta_b,
+            token_program,
+            mint_b_decimals,
+        })
+    }
+}
+
+impl<'a, 'info> Offer<'a, 'info> {
+    // validates the escrow without moving any funds
+    pub fn load(&self, program_id: &Pubkey, taker: &Taker) -> Result<Escrow, ProgramError> {
+        assert_eq!(self.escrow.owner, program_id);
+        let escrow_data = *Escrow::load(&self.escrow.try_borrow_data()?)?;
+
+        let escrow_seeds = &[b"escrow", self.maker.key.as_ref(), &[escrow_data.bump]];
+        let expected_escrow = Pubkey::create_program_address(escrow_seeds, program_id)?; // save cu by using this instead of find_program_address
+        assert_eq!(&expected_escrow, self.escrow.key);
+
+        // the offer has to be for the pair the taker passed in,
+        // a collection bid takes any NFT of its collection as mint b
+        assert_eq!(&escrow_data.mint_a, taker.mint_a.key);
+        match (EscrowKind::try_from(escrow_data.kind)?, self.metadata) {
+            (EscrowKind::CollectionBid, Some(metadata)) => {
+                check_nft_mint(taker.mint_b)?;
+                check_collection(taker.mint_b, metadata, &escrow_data.collection)?;
+            }
+            (EscrowKind::CollectionBid, None) => return Err(ProgramError::NotEnoughAccountKeys),
+            (_, Some(_)) => return Err(ProgramError::InvalidArgument),
+            (_, None) => assert_eq!(&escrow_data.mint_b, taker.mint_b.key),
+        }
+        // mint b has to reach the maker, for a collection bid the maker prepared a token account for the NFT
+        check_token_account(self.maker_ta_b, taker.mint_b.key, self.maker.key)?;
+        check_vault(self.vault, &escrow_data.mint_a, self.escrow.key)?;
+
+        // a listed offer has to leave the index with the escrow
+        if escrow_data.indexed != 0 && self.index.is_none() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // every basket leg has to be passed in, in the order make stored them
+        let basket_mints = Escrow::basket(&self.escrow.try_borrow_data()?)?.to_vec();
+        if self.basket.len() != basket_mints.len() * BASKET_ACCOUNTS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        for (leg, basket_mint) in self.basket.chunks_exact(BASKET_ACCOUNTS).zip(basket_mints.iter()) {
+            let [mint, taker_ta, basket_vault] = leg else {
+                unreachable!()
+            };
+            assert_eq!(mint.key, basket_mint);
+            check_vault(basket_vault, mint.key, self.escrow.key)?;
+
+            // a delegate can't route the basket anywhere but to the taker
+            if taker.authority.key != taker.taker.key {
+                check_token_account(taker_ta, mint.key, taker.taker.key)?;
+            }
+        }
+
+        Ok(escrow_data)
+    }
+
+    // swaps the vaults for `receive` and closes escrow and vaults, `escrow_data` has to come from `load`
+    pub fn fill(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        taker: &Taker<'_, 'info>,
+        escrow_data: &Escrow,
+        receive: u64,
+    ) -> ProgramResult {
+        let escrow_seeds: &[&[u8]] = &[b"escrow", self.maker.key.as_ref(), &[escrow_data.bump]];
+
+        // Transfer A from vault to taker_ta_a and close the vault
+        let a_amount = release_vault(
+            taker.token_program,
+            self.escrow,
+            self.vault,
+            taker.mint_a,
+            taker.taker_ta_a,
+            self.maker,
+            escrow_seeds,
+        )?;
+
+        let mut basket_legs = Vec::with_capacity(escrow_data.extra_mints as usize);
+        for leg in self.basket.chunks_exact(BASKET_ACCOUNTS) {
+            let [mint, taker_ta, basket_vault] = leg else {
+                unreachable!()
+            };
+
+            let amount = release_vault(
+                taker.token_program,
+                self.escrow,
+                basket_vault,
+                mint,
+                taker_ta,
+                self.maker,
+                escrow_seeds,
+            )?;
+
+            basket_legs.push(BasketLeg {
+                mint: *mint.key,
+                amount,
+            });
+        }
+
+        // a delegate can only spend what the taker approved
+        if taker.authority.key != taker.taker.key {
+            assert!(Account::unpack(&taker.taker_ta_b.try_borrow_data()?)?.delegated_amount >= receive);
+        }
+
+        // Transfer B from taker_ta_b
+        invoke(
+            &transfer_checked(
+                taker.token_program.key,
+                taker.taker_ta_b.key,
+                taker.mint_b.key,
+                self.maker_ta_b.key,
+                taker.authority.key,
+                &[],
+                receive,
+                taker.mint_b_decimals,
+            )?,
+            accounts,
+        )?;
+
+        if let (true, Some(index)) = (escrow_data.indexed != 0, self.index) {
+            order_book_remove(&crate::id(), index, escrow_data, self.escrow.key, self.maker)?;
+        }
+
+        // close escrow
+        close_program_account(self.escrow, self.maker)?;
+
+        EscrowTaken {
+            escrow: *self.escrow.key,
+            maker: *self.maker.key,
+            taker: *taker.taker.key,
+            delegate: (taker.authority.key != taker.taker.key).then_some(*taker.authority.key),
+            mint_a: *taker.mint_a.key,
+            mint_b: *taker.mint_b.key,
+            amount: a_amount,
+            receive,
+            basket: basket_legs,
+        }.emit()?;
+
+        Ok(())
+    }
+}
 
 pub fn take(
-    program_id: &Pubkey, 
-    accounts: &[AccountInfo]
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_receive: u64,
+    delegated: bool,
 ) -> ProgramResult {
 
+    // a delegated take is signed by the delegate, passed in front of the take accounts
+    let (delegate, take_accounts) = match (delegated, accounts) {
+        (true, [delegate, take_accounts @ ..]) => (Some(delegate), take_accounts),
+        (true, []) => return Err(ProgramError::NotEnoughAccountKeys),
+        (false, _) => (None, accounts),
+    };
+
     let [
-        taker, 
+        taker,
         maker,
         mint_a,
         mint_b, // pubkey is enought, we don't need token info
@@ -27,8 +254,9 @@ pub fn take(
         taker_ta_b,
         vault,
         token_program,
-        system_program
-     ] = accounts else {
+        system_program,
+        remaining @ .. // the pair's index if listed, mint b's metadata for a collection bid, then (mint, taker_ta, vault) per basket leg
+     ] = take_accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -36,77 +264,45 @@ pub fn take(
     // possibly could leave that out -- why?
     assert!(system_program::check_id(system_program.key));
 
-    // check that token program is correct
-    assert!(spl_token::check_id(token_program.key));
-
     // check that own program id is correct
     assert!(crate::check_id(program_id));
 
-    // check that maker is signer
-    assert!(taker.is_signer);
-
-    assert!(taker.is_writable);
-
-    let mint_a_decimals = Mint::unpack(&mint_a.try_borrow_data()?)?.decimals;
-    let mint_b_decimals = Mint::unpack(&mint_b.try_borrow_data()?)?.decimals;
-
-    let escrow_data = *bytemuck::try_from_bytes::<Escrow>(*escrow.data.borrow())
-        .map_err(|_| ProgramError::AccountBorrowFailed)?;
-    let escrow_seeds = &[b"escrow", maker.key.as_ref(), &[escrow_data.bump as u8]];
-
-    let b_amount = spl_token::state::Account::unpack(&vault.try_borrow_data()?)?.amount;
-
-    // Transfer A from vault to taker_ta_a
-    invoke_signed(
-        &transfer_checked(
-            token_program.key,
-            vault.key,
-            mint_a.key,
-            taker_ta_a.key,
-            escrow.key,
-            &[],
-            b_amount,
-            mint_a_decimals,
-        )?,
-        accounts,
-        &[escrow_seeds]
+    let taker = Taker::new(
+        delegate,
+        taker,
+        mint_a,
+        mint_b,
+        taker_ta_a,
+        taker_ta_b,
+        token_program,
     )?;
 
-    // Transfer B from taker_ta_b
-    invoke(
-        &transfer_checked(
-            token_program.key,
-            taker_ta_b.key,
-            mint_b.key,
-            maker_ta_b.key,
-            taker.key,
-            &[],
-            escrow_data.receive,
-            mint_b_decimals,
-        )?,
-        accounts,
-    )?;
+    // only peeks at the escrow to split the accounts, Offer::load validates it
+    let escrow_header = *Escrow::load(&escrow.try_borrow_data()?)?;
+    let (index, remaining) = match remaining {
+        [index, remaining @ ..] if escrow_header.indexed != 0 => (Some(index), remaining),
+        _ => (None, remaining),
+    };
+    let (metadata, basket) = match remaining {
+        [metadata, basket @ ..] if escrow_header.kind == EscrowKind::CollectionBid as u8 => (Some(metadata), basket),
+        _ => (None, remaining),
+    };
 
-    // close escrow
-    let mut escrow_data = escrow.data.borrow_mut();
-    escrow_data.fill(0);
-    let maker_orig_lamports = maker.lamports();
-    **maker.lamports.borrow_mut() = maker_orig_lamports.checked_add(escrow.lamports()).ok_or(ProgramError::ArithmeticOverflow)?;
-    **escrow.lamports.borrow_mut() = 0;
-
-    // close vault
-
-    invoke_signed(
-        &close_account(
-            token_program.key,
-            vault.key,
-            maker.key,
-            escrow.key,
-            &[],
-        )?,
-        accounts,
-        &[escrow_seeds]
-    )?;
+    let offer = Offer {
+        escrow,
+        maker,
+        maker_ta_b,
+        vault,
+        index,
+        metadata,
+        basket,
+    };
+
+    let escrow_data = offer.load(program_id, &taker)?;
+
+    // dutch auctions decay over time, the taker never pays more than max_receive
+    let receive = escrow_data.price(Clock::get()?.unix_timestamp)?;
+    assert!(receive <= max_receive);
 
-    Ok(())
-}
\ No newline at end of file
+    offer.fill(accounts, &taker, &escrow_data, receive)
+}
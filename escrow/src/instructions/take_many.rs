@@ -0,0 +1,99 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    system_program,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::instructions::take::{Offer, Taker};
+
+// accounts per offer after the fixed accounts: escrow, maker, maker_ta_b, vault
+const OFFER_ACCOUNTS: usize = 4;
+
+pub fn take_many(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    count: u8,
+    max_receive: u64,
+) -> ProgramResult {
+
+    let [
+        taker,
+        mint_a,
+        mint_b,
+        taker_ta_a,
+        taker_ta_b,
+        token_program,
+        system_program,
+        offers @ .. // then the pair's index if any offer is listed
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    assert!(count > 0);
+    let (offers, index) = match offers.len().checked_sub(count as usize * OFFER_ACCOUNTS) {
+        Some(0) => (offers, None),
+        Some(1) => (&offers[..offers.len() - 1], offers.last()),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+
+    // check that system program is correct
+    assert!(system_program::check_id(system_program.key));
+
+    // check that own program id is correct
+    assert!(crate::check_id(program_id));
+
+    let taker = Taker::new(
+        None,
+        taker,
+        mint_a,
+        mint_b,
+        taker_ta_a,
+        taker_ta_b,
+        token_program,
+    )?;
+
+    let offers = offers
+        .chunks_exact(OFFER_ACCOUNTS)
+        .map(|offer| {
+            let [escrow, maker, maker_ta_b, vault] = offer else {
+                unreachable!()
+            };
+            Offer {
+                escrow,
+                maker,
+                maker_ta_b,
+                vault,
+                index,
+                metadata: None, // collection bids have to be taken one by one
+                basket: &[], // basket offers have to be taken one by one
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // validate every offer before the first transfer, so a bad one fails the batch up front
+    let escrows = offers
+        .iter()
+        .map(|offer| offer.load(program_id, &taker))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // max_receive bounds the total paid in mint b over all offers
+    let now = Clock::get()?.unix_timestamp;
+    let prices = escrows
+        .iter()
+        .map(|escrow_data| escrow_data.price(now))
+        .collect::<Result<Vec<_>, _>>()?;
+    let total = prices
+        .iter()
+        .try_fold(0u64, |total, price| total.checked_add(*price))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    assert!(total <= max_receive);
+
+    for ((offer, escrow_data), receive) in offers.iter().zip(escrows.iter()).zip(prices) {
+        offer.fill(accounts, &taker, escrow_data, receive)?;
+    }
+
+    Ok(())
+}
@@ -1,16 +1,19 @@
-use solana_program::{
-    entrypoint,
-    declare_id,
-};
-use processor::process_instruction;
+use solana_program::declare_id;
+pub use processor::process_instruction;
 
 mod tests;
-mod state;
+pub mod state;
+pub mod events;
+pub mod cpi;
 mod instructions;
+mod metadata;
 mod processor;
+mod utils;
 
 
 // gives you the option to do check_id on the program id
 declare_id!("22222222222222222222222222222222222222222222");
 
-entrypoint!(process_instruction);
+// programs calling in through `cpi` link this crate with no-entrypoint
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
@@ -0,0 +1,98 @@
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+// the parts of a Metaplex token metadata account escrow needs, read by hand
+// so the program doesn't pull in the whole metadata crate
+
+pub const METADATA_PROGRAM_ID: Pubkey = Pubkey::new_from_array(five8_const::decode_32_const(
+    "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s",
+));
+
+const KEY_METADATA_V1: u8 = 4;
+const CREATOR_LEN: usize = 32 + 1 + 1; // address, verified, share
+
+// metadata PDA of [b"metadata", METADATA_PROGRAM_ID, mint]
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()], &METADATA_PROGRAM_ID).0
+}
+
+// the collection `mint` belongs to, only if the collection authority verified it
+pub fn verified_collection(data: &[u8], mint: &Pubkey) -> Result<Option<Pubkey>, ProgramError> {
+    let mut reader = Reader { data };
+
+    if reader.u8()? != KEY_METADATA_V1 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    reader.skip(32)?; // update authority
+    if reader.pubkey()? != *mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // name, symbol, uri
+    for _ in 0..3 {
+        let len = reader.u32()? as usize;
+        reader.skip(len)?;
+    }
+    reader.skip(2)?; // seller fee basis points
+    if reader.option()? {
+        let creators = reader.u32()? as usize;
+        reader.skip(creators.checked_mul(CREATOR_LEN).ok_or(ProgramError::InvalidAccountData)?)?;
+    }
+    reader.skip(2)?; // primary sale happened, is mutable
+    if reader.option()? {
+        reader.skip(1)?; // edition nonce
+    }
+    if reader.option()? {
+        reader.skip(1)?; // token standard
+    }
+
+    if !reader.option()? {
+        return Ok(None);
+    }
+    let verified = reader.u8()? != 0;
+    let collection = reader.pubkey()?;
+
+    Ok(verified.then_some(collection))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProgramError> {
+        if self.data.len() < len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), ProgramError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, ProgramError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ProgramError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey, ProgramError> {
+        Ok(Pubkey::new_from_array(self.take(32)?.try_into().unwrap()))
+    }
+
+    // borsh Option tag, older accounts can end before the optional fields
+    fn option(&mut self) -> Result<bool, ProgramError> {
+        match self.data.first() {
+            None => Ok(false),
+            Some(_) => match self.u8()? {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(ProgramError::InvalidAccountData),
+            },
+        }
+    }
+}
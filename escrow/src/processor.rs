@@ -5,21 +5,66 @@ use solana_program::{
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use crate::instructions;
+use crate::{instructions, state::DutchAuction};
 
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct EscrowArgs {
     pub maker: Pubkey,
     pub amount: u64,
     pub receive: u64,
-    pub escrow_bump: u8, // to save comutation when finding the programaddress
+    pub escrow_bump: u8, // must be the canonical bump, make rejects anything else
+    pub basket_amounts: Vec<u64>, // one per basket leg in the remaining accounts, empty for a single mint offer
+    pub terms: EscrowTerms,
+    pub authority: Option<Pubkey>, // Multisig that approves refund and release instead of the maker, passed after the basket accounts with its signers
+    pub nft: Option<NftArgs>, // mint a is a single NFT, None for fungible offers
+    pub index: bool, // list the offer in the OrderBook of its pair, passed first in the remaining accounts
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct NftArgs {
+    pub collection: Option<Pubkey>, // checked against mint a's metadata account, passed after the basket accounts
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum EscrowTerms {
+    Fixed,
+    Dutch(DutchAuction), // start_receive has to equal receive
+    English { end_ts: i64 }, // receive is the reserve price
+    Arbitrated { beneficiary: Pubkey, arbiter: Pubkey }, // settled by release or the arbiter, not by take
+    CollectionBid { collection: Pubkey }, // mint_b is the default pubkey and receive 1, take swaps in any NFT verified in the collection
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VestingArgs {
+    pub amount: u64,
+    pub beneficiary: Pubkey,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+    pub escrow_bump: u8, // must be the canonical bump, make_vesting rejects anything else
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, PartialEq, Eq)]
+pub enum ArbiterDecision {
+    Maker,
+    Beneficiary,
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
 pub enum EscrowInstruction {
    Make(EscrowArgs),
-   Take,
+   Take { max_receive: u64 }, // slippage bound on the price paid in mint b
    Refund,
+   TakeMany { count: u8, max_receive: u64 }, // accounts: taker side of take, then count x (escrow, maker, maker_ta_b, vault)
+   Bid { amount: u64 },
+   Settle,
+   Release,
+   Dispute,
+   Resolve(ArbiterDecision),
+   MakeVesting(VestingArgs),
+   Claim,
+   InitMultisig { m: u8 }, // accounts: multisig, then the n signers
+   TakeDelegated { max_receive: u64 }, // accounts: delegate with an approval on taker_ta_b, then the take accounts
 }
 
 
@@ -33,8 +78,18 @@ pub fn process_instruction(
 
     match instruction {
         EscrowInstruction::Make(escrow_args) => instructions::make(program_id, accounts, escrow_args),
-        EscrowInstruction::Take => instructions::take(program_id, accounts),
+        EscrowInstruction::Take { max_receive } => instructions::take(program_id, accounts, max_receive, false),
         EscrowInstruction::Refund => instructions::refund(program_id, accounts),
+        EscrowInstruction::TakeMany { count, max_receive } => instructions::take_many(program_id, accounts, count, max_receive),
+        EscrowInstruction::Bid { amount } => instructions::bid(program_id, accounts, amount),
+        EscrowInstruction::Settle => instructions::settle(program_id, accounts),
+        EscrowInstruction::Release => instructions::release(program_id, accounts),
+        EscrowInstruction::Dispute => instructions::dispute(program_id, accounts),
+        EscrowInstruction::Resolve(decision) => instructions::resolve(program_id, accounts, decision),
+        EscrowInstruction::MakeVesting(vesting_args) => instructions::make_vesting(program_id, accounts, vesting_args),
+        EscrowInstruction::Claim => instructions::claim(program_id, accounts),
+        EscrowInstruction::InitMultisig { m } => instructions::init_multisig(program_id, accounts, m),
+        EscrowInstruction::TakeDelegated { max_receive } => instructions::take(program_id, accounts, max_receive, true),
     }
 
 }
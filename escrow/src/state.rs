@@ -1,16 +1,348 @@
+use borsh::{BorshDeserialize, BorshSerialize};
 use bytemuck::{Pod, Zeroable};
-use solana_program::pubkey::Pubkey;
+use std::cmp::Ordering;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
 
+// account layout: Escrow header, followed by `extra_mints` basket mints (one Pubkey each)
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct Escrow {
+    pub discriminator: [u8; 8], // always at offset 0 so clients can memcmp on it
+    pub version: u8,
+    pub bump: u8, // canonical bump, checked in make
+    pub extra_mints: u8, // basket mints deposited next to mint_a
+    pub kind: u8, // EscrowKind
+    pub nft: u8, // mint_a is a single NFT, checked in make
+    pub indexed: u8, // listed in the OrderBook of its pair, take and refund remove it
+    pub _padding: [u8; 2], // keeps the pubkeys 8 byte aligned, no implicit padding for Pod
     pub maker: Pubkey,
+    pub authority: Pubkey, // approves refund and release, the maker itself or a Multisig
     pub mint_a: Pubkey,
-    pub mint_b: Pubkey,
-    pub receive: u64,
-    pub bump: u64, 
+    pub mint_b: Pubkey, // default pubkey for collection bids, any NFT of `collection` is taken instead
+    pub collection: Pubkey, // verified collection of the NFT, or the one a collection bid asks for, default pubkey if none was checked
+    pub receive: u64, // listed price, the starting price for dutch auctions
+    pub dutch: DutchAuction, // zeroed unless kind is Dutch
+    pub english: EnglishAuction, // zeroed unless kind is English
+    pub arbitration: Arbitration, // zeroed unless kind is Arbitrated
+    pub vesting: Vesting, // zeroed unless kind is Vesting
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EscrowKind {
+    Fixed,
+    Dutch,
+    English,
+    Arbitrated,
+    Vesting,
+    CollectionBid,
+}
+
+impl TryFrom<u8> for EscrowKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Fixed),
+            1 => Ok(Self::Dutch),
+            2 => Ok(Self::English),
+            3 => Ok(Self::Arbitrated),
+            4 => Ok(Self::Vesting),
+            5 => Ok(Self::CollectionBid),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
 }
 
-impl Escrow {   
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, BorshDeserialize, BorshSerialize, PartialEq, Eq, Debug)]
+pub struct DutchAuction {
+    pub start_receive: u64,
+    pub end_receive: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+impl DutchAuction {
+    pub fn is_valid(&self) -> bool {
+        self.start_ts < self.end_ts && self.end_receive <= self.start_receive
+    }
+
+    // linear decay from start_receive at start_ts to end_receive at end_ts,
+    // the price stays at the bounds outside of that window
+    pub fn price(&self, now: i64) -> u64 {
+        if now <= self.start_ts {
+            return self.start_receive;
+        }
+        if now >= self.end_ts {
+            return self.end_receive;
+        }
+
+        // widen before subtracting, timestamps can be anywhere in the i64 range
+        let elapsed = (now as i128 - self.start_ts as i128) as u128;
+        let duration = (self.end_ts as i128 - self.start_ts as i128) as u128;
+        let decay = (self.start_receive - self.end_receive) as u128 * elapsed / duration;
+
+        self.start_receive - decay as u64
+    }
+}
+
+// ascending auction, `receive` is the reserve price and bids are held in per-bid vaults
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct EnglishAuction {
+    pub highest_bidder: Pubkey, // default pubkey while there is no bid
+    pub highest_bid: u64,
+    pub end_ts: i64, // bids are accepted until end_ts, settle only after
+}
+
+// escrow for a service contract: the maker releases to the beneficiary,
+// or after a dispute the arbiter resolves to either side
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct Arbitration {
+    pub beneficiary: Pubkey,
+    pub arbiter: Pubkey,
+    pub disputed: u8,
+    pub _padding: [u8; 7],
+}
+
+// vault unlocks to the beneficiary linearly from start_ts to end_ts, nothing before cliff_ts
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, PartialEq, Eq, Debug)]
+pub struct Vesting {
+    pub beneficiary: Pubkey,
+    pub total: u64,
+    pub claimed: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+}
+
+impl Vesting {
+    pub fn is_valid(&self) -> bool {
+        self.start_ts <= self.cliff_ts && self.cliff_ts <= self.end_ts && self.start_ts < self.end_ts
+    }
+
+    // total amount unlocked at `now`, claimed included
+    pub fn unlocked(&self, now: i64) -> u64 {
+        if now < self.cliff_ts {
+            return 0;
+        }
+        if now >= self.end_ts {
+            return self.total;
+        }
+
+        // widen before subtracting, timestamps can be anywhere in the i64 range
+        let elapsed = (now as i128 - self.start_ts as i128) as u128;
+        let duration = (self.end_ts as i128 - self.start_ts as i128) as u128;
+
+        (self.total as u128 * elapsed / duration) as u64
+    }
+
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.unlocked(now).saturating_sub(self.claimed)
+    }
+}
+
+impl Escrow {
     pub const LEN: usize = std::mem::size_of::<Escrow>();
-}
\ No newline at end of file
+    pub const DISCRIMINATOR: [u8; 8] = *b"escrow\0\0";
+    pub const VERSION: u8 = 11;
+    pub const MAX_EXTRA_MINTS: usize = 3; // basket of up to 4 mints including mint_a
+
+    pub const fn space(extra_mints: usize) -> usize {
+        Self::LEN + extra_mints * std::mem::size_of::<Pubkey>()
+    }
+
+    // checks the type tag and layout version before handing out the escrow,
+    // so no other program owned account can be read as an escrow
+    pub fn load(data: &[u8]) -> Result<&Escrow, ProgramError> {
+        let header = data.get(..Self::LEN).ok_or(ProgramError::InvalidAccountData)?;
+        let escrow = bytemuck::try_from_bytes::<Escrow>(header)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if escrow.discriminator != Self::DISCRIMINATOR || escrow.version != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if data.len() != Self::space(escrow.extra_mints as usize) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(escrow)
+    }
+
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Escrow, ProgramError> {
+        Self::load(data)?;
+        bytemuck::try_from_bytes_mut::<Escrow>(&mut data[..Self::LEN])
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // what the taker has to pay at `now`
+    pub fn price(&self, now: i64) -> Result<u64, ProgramError> {
+        match EscrowKind::try_from(self.kind)? {
+            EscrowKind::Fixed | EscrowKind::CollectionBid => Ok(self.receive),
+            EscrowKind::Dutch => Ok(self.dutch.price(now)),
+            // english auctions go through bid and settle, arbitrated escrows through release and resolve,
+            // vesting escrows are claimed by the beneficiary
+            EscrowKind::English | EscrowKind::Arbitrated | EscrowKind::Vesting => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    // the basket mints stored after the header
+    pub fn basket(data: &[u8]) -> Result<&[Pubkey], ProgramError> {
+        Self::load(data)?;
+        bytemuck::try_cast_slice::<u8, Pubkey>(&data[Self::LEN..])
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// a single bid on an english auction, PDA of [b"bid", escrow, bidder],
+// the bid amount sits in a token account for mint_b owned by this PDA
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct Bid {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub bump: u8,
+    pub _padding: [u8; 6],
+    pub escrow: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+impl Bid {
+    pub const LEN: usize = std::mem::size_of::<Bid>();
+    pub const DISCRIMINATOR: [u8; 8] = *b"bid\0\0\0\0\0";
+    pub const VERSION: u8 = 1;
+
+    pub fn load(data: &[u8]) -> Result<&Bid, ProgramError> {
+        let bid = bytemuck::try_from_bytes::<Bid>(data)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if bid.discriminator != Self::DISCRIMINATOR || bid.version != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(bid)
+    }
+}
+
+// M-of-N authority, an account created by the client for this program and set up by InitMultisig
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct Multisig {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub m: u8, // signatures required
+    pub n: u8, // signers listed
+    pub _padding: [u8; 5],
+    pub signers: [Pubkey; Multisig::MAX_SIGNERS],
+}
+
+impl Multisig {
+    pub const LEN: usize = std::mem::size_of::<Multisig>();
+    pub const DISCRIMINATOR: [u8; 8] = *b"multisig";
+    pub const VERSION: u8 = 1;
+    pub const MAX_SIGNERS: usize = 11; // same as spl-token
+
+    pub fn load(data: &[u8]) -> Result<&Multisig, ProgramError> {
+        let multisig = bytemuck::try_from_bytes::<Multisig>(data)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if multisig.discriminator != Self::DISCRIMINATOR || multisig.version != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(multisig)
+    }
+
+    // counts every listed signer once, no matter how often it's passed in
+    pub fn is_approved_by<'a>(&self, signers: impl Iterator<Item = &'a Pubkey>) -> bool {
+        let listed = &self.signers[..self.n as usize];
+        let mut approved = [false; Self::MAX_SIGNERS];
+
+        for signer in signers {
+            if let Some(position) = listed.iter().position(|key| key == signer) {
+                approved[position] = true;
+            }
+        }
+
+        approved.iter().filter(|approved| **approved).count() >= self.m as usize
+    }
+}
+
+// open offers of one (mint_a, mint_b) pair, PDA of [b"index", mint_a, mint_b],
+// followed by `len` entries, the account grows and shrinks with them
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct OrderBook {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub bump: u8,
+    pub _padding: [u8; 2],
+    pub len: u32,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable, PartialEq, Eq, Debug)]
+pub struct OrderBookEntry {
+    pub escrow: Pubkey,
+    pub amount: u64, // of mint_a in the vault
+    pub receive: u64, // listed price, the starting price for dutch auctions
+}
+
+impl OrderBook {
+    pub const LEN: usize = std::mem::size_of::<OrderBook>();
+    pub const ENTRY_LEN: usize = std::mem::size_of::<OrderBookEntry>();
+    pub const DISCRIMINATOR: [u8; 8] = *b"orders\0\0";
+    pub const VERSION: u8 = 1;
+    pub const MAX_ENTRIES: usize = 128;
+
+    pub const fn space(len: usize) -> usize {
+        Self::LEN + len * Self::ENTRY_LEN
+    }
+
+    pub fn load(data: &[u8]) -> Result<&OrderBook, ProgramError> {
+        let header = data.get(..Self::LEN).ok_or(ProgramError::InvalidAccountData)?;
+        let order_book = bytemuck::try_from_bytes::<OrderBook>(header)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if order_book.discriminator != Self::DISCRIMINATOR || order_book.version != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if data.len() != Self::space(order_book.len as usize) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(order_book)
+    }
+
+    // entries are unordered, removing one moves the last into its place
+    pub fn entries(data: &[u8]) -> Result<&[OrderBookEntry], ProgramError> {
+        Self::load(data)?;
+        bytemuck::try_cast_slice::<u8, OrderBookEntry>(&data[Self::LEN..])
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // the offer with the lowest listed price per unit of mint_a
+    pub fn best_offer(data: &[u8]) -> Result<Option<&OrderBookEntry>, ProgramError> {
+        Ok(Self::entries(data)?.iter().min_by(|a, b| a.cmp_price(b)))
+    }
+
+    // the position of the offer with the highest listed price per unit of mint_a
+    pub fn worst_offer(data: &[u8]) -> Result<Option<(usize, &OrderBookEntry)>, ProgramError> {
+        Ok(Self::entries(data)?.iter().enumerate().max_by(|(_, a), (_, b)| a.cmp_price(b)))
+    }
+}
+
+impl OrderBookEntry {
+    // self.receive / self.amount against other.receive / other.amount without dividing
+    pub fn cmp_price(&self, other: &Self) -> Ordering {
+        (self.receive as u128 * other.amount as u128).cmp(&(other.receive as u128 * self.amount as u128))
+    }
+}
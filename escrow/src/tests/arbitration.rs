@@ -0,0 +1,266 @@
+use mollusk_svm::Mollusk;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::{
+    processor::{ArbiterDecision, EscrowInstruction},
+    state::{Escrow, EscrowKind},
+    tests::{
+        setup_mollusk,
+        multisig_authority,
+        create_token_account,
+        instruction_data,
+        token_amount,
+        Swap,
+        AMOUNT,
+    },
+};
+
+// the parties of an arbitrated escrow next to the maker
+struct Parties {
+    beneficiary: SolanaPubkey,
+    beneficiary_ta_a: SolanaPubkey,
+    arbiter: SolanaPubkey,
+}
+
+// an arbitrated escrow of AMOUNT mint a
+fn arbitrated_escrow(mollusk: &Mollusk) -> (Swap, Parties) {
+    let mut swap = Swap::new(mollusk);
+    let parties = Parties {
+        beneficiary: SolanaPubkey::new_unique(),
+        beneficiary_ta_a: SolanaPubkey::new_unique(),
+        arbiter: SolanaPubkey::new_unique(),
+    };
+
+    swap.escrow_data.kind = EscrowKind::Arbitrated as u8;
+    swap.escrow_data.arbitration.beneficiary = parties.beneficiary;
+    swap.escrow_data.arbitration.arbiter = parties.arbiter;
+
+    swap.set_account(parties.beneficiary, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+    swap.set_account(parties.beneficiary_ta_a, create_token_account(parties.beneficiary, mollusk, swap.mint_a, 0));
+    swap.set_account(parties.arbiter, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+
+    (swap, parties)
+}
+
+fn setup_release(
+    mollusk: &Mollusk,
+    swap: &Swap,
+    parties: &Parties,
+    maker_signs: bool,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let account_metas = vec![
+        AccountMeta::new(swap.maker, maker_signs),
+        AccountMeta::new_readonly(parties.beneficiary, false),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new(swap.vault, false),
+        AccountMeta::new(parties.beneficiary_ta_a, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Release), account_metas)
+}
+
+fn setup_dispute(
+    mollusk: &Mollusk,
+    swap: &Swap,
+    disputer: SolanaPubkey,
+    disputer_signs: bool,
+    authority_metas: Vec<AccountMeta>,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let mut account_metas = vec![
+        AccountMeta::new_readonly(disputer, disputer_signs),
+        AccountMeta::new(swap.escrow, false),
+    ];
+    account_metas.extend(authority_metas);
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Dispute), account_metas)
+}
+
+fn setup_resolve(
+    mollusk: &Mollusk,
+    swap: &Swap,
+    arbiter: SolanaPubkey,
+    recipient_ta_a: SolanaPubkey,
+    decision: ArbiterDecision,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let account_metas = vec![
+        AccountMeta::new_readonly(arbiter, true),
+        AccountMeta::new(swap.maker, false),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new(swap.vault, false),
+        AccountMeta::new(recipient_ta_a, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Resolve(decision)), account_metas)
+}
+
+#[test]
+fn release_pays_beneficiary(){
+    let mollusk = setup_mollusk();
+    let (swap, parties) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = setup_release(&mollusk, &swap, &parties, true);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&parties.beneficiary_ta_a).unwrap()), AMOUNT);
+    assert_eq!(result.get_account(&swap.escrow).unwrap().lamports(), 0);
+}
+
+#[test]
+fn release_rejects_without_maker_signature(){
+    let mollusk = setup_mollusk();
+    let (swap, parties) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = setup_release(&mollusk, &swap, &parties, false);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn refund_rejects_arbitrated_escrow(){
+    let mollusk = setup_mollusk();
+    let (swap, _) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = swap.refund(&mollusk, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn dispute_marks_escrow_disputed(){
+    let mollusk = setup_mollusk();
+    let (swap, parties) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = setup_dispute(&mollusk, &swap, parties.beneficiary, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().arbitration.disputed, 1);
+}
+
+#[test]
+fn dispute_rejects_third_party(){
+    let mollusk = setup_mollusk();
+    let (swap, parties) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = setup_dispute(&mollusk, &swap, parties.arbiter, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn dispute_by_maker(){
+    let mollusk = setup_mollusk();
+    let (swap, _) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = setup_dispute(&mollusk, &swap, swap.maker, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().arbitration.disputed, 1);
+}
+
+#[test]
+fn dispute_rejects_beneficiary_without_signature(){
+    let mollusk = setup_mollusk();
+    let (swap, parties) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = setup_dispute(&mollusk, &swap, parties.beneficiary, false, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn dispute_by_maker_multisig(){
+    let mollusk = setup_mollusk();
+    let (mut swap, _) = arbitrated_escrow(&mollusk);
+    let (multisig, signers) = multisig_authority(&mollusk, &mut swap);
+
+    // the maker doesn't sign, two of the three signers do
+    let authority_metas = vec![
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new_readonly(signers[0], true),
+        AccountMeta::new_readonly(signers[2], true),
+    ];
+    let (instruction, accounts) = setup_dispute(&mollusk, &swap, swap.maker, false, authority_metas);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().arbitration.disputed, 1);
+}
+
+#[test]
+fn dispute_rejects_maker_key_alone_with_multisig_authority(){
+    let mollusk = setup_mollusk();
+    let (mut swap, _) = arbitrated_escrow(&mollusk);
+    multisig_authority(&mollusk, &mut swap);
+
+    // the maker signs, but the multisig controls the escrow
+    let (instruction, accounts) = setup_dispute(&mollusk, &swap, swap.maker, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn dispute_rejects_multisig_below_threshold(){
+    let mollusk = setup_mollusk();
+    let (mut swap, _) = arbitrated_escrow(&mollusk);
+    let (multisig, signers) = multisig_authority(&mollusk, &mut swap);
+
+    let authority_metas = vec![
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new_readonly(signers[0], true),
+    ];
+    let (instruction, accounts) = setup_dispute(&mollusk, &swap, swap.maker, false, authority_metas);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn resolve_pays_side_of_the_decision(){
+    let mollusk = setup_mollusk();
+    let (mut swap, parties) = arbitrated_escrow(&mollusk);
+    swap.escrow_data.arbitration.disputed = 1;
+
+    let (instruction, accounts) = setup_resolve(&mollusk, &swap, parties.arbiter, swap.maker_ta_a, ArbiterDecision::Maker);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(result.get_account(&swap.escrow).unwrap().lamports(), 0);
+}
+
+#[test]
+fn resolve_rejects_undisputed_escrow(){
+    let mollusk = setup_mollusk();
+    let (swap, parties) = arbitrated_escrow(&mollusk);
+
+    let (instruction, accounts) = setup_resolve(&mollusk, &swap, parties.arbiter, parties.beneficiary_ta_a, ArbiterDecision::Beneficiary);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn resolve_rejects_recipient_against_the_decision(){
+    let mollusk = setup_mollusk();
+    let (mut swap, parties) = arbitrated_escrow(&mollusk);
+    swap.escrow_data.arbitration.disputed = 1;
+
+    // decided for the maker, paid to the beneficiary
+    let (instruction, accounts) = setup_resolve(&mollusk, &swap, parties.arbiter, parties.beneficiary_ta_a, ArbiterDecision::Maker);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
@@ -0,0 +1,239 @@
+use mollusk_svm::Mollusk;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::{
+    processor::EscrowInstruction,
+    state::{Escrow, EscrowKind},
+    tests::{
+        setup_mollusk,
+        create_token_account,
+        instruction_data,
+        token_amount,
+        Swap,
+        AMOUNT,
+        PROGRAM_ID,
+        RECEIVE,
+    },
+};
+
+// bids are accepted until END_TS, settle only from END_TS on
+const END_TS: i64 = 1_000;
+const BALANCE: u64 = 1_000;
+
+// a bidder holding BALANCE of mint b, with its bid PDA and the bid vault
+struct Bidder {
+    key: SolanaPubkey,
+    ta_a: SolanaPubkey,
+    ta_b: SolanaPubkey,
+    bid: SolanaPubkey,
+    bid_vault: SolanaPubkey,
+}
+
+// an english auction of AMOUNT mint a with RECEIVE as reserve price
+fn english_auction(mollusk: &Mollusk) -> Swap {
+    let mut swap = Swap::new(mollusk);
+    swap.escrow_data.kind = EscrowKind::English as u8;
+    swap.escrow_data.english.end_ts = END_TS;
+    swap
+}
+
+fn add_bidder(mollusk: &Mollusk, swap: &mut Swap) -> Bidder {
+    let key = SolanaPubkey::new_unique();
+    let (bid, _) = SolanaPubkey::find_program_address(&[b"bid", swap.escrow.as_ref(), key.as_ref()], &PROGRAM_ID);
+    let bidder = Bidder {
+        key,
+        ta_a: SolanaPubkey::new_unique(),
+        ta_b: SolanaPubkey::new_unique(),
+        bid,
+        bid_vault: SolanaPubkey::new_unique(),
+    };
+
+    swap.set_account(bidder.key, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+    swap.set_account(bidder.ta_a, create_token_account(bidder.key, mollusk, swap.mint_a, 0));
+    swap.set_account(bidder.ta_b, create_token_account(bidder.key, mollusk, swap.mint_b, BALANCE));
+    swap.set_account(bidder.bid, AccountSharedData::default());
+    swap.set_account(bidder.bid_vault, create_token_account(bidder.bid, mollusk, swap.mint_b, 0));
+
+    bidder
+}
+
+fn setup_bid(
+    mollusk: &Mollusk,
+    swap: &Swap,
+    bidder: &Bidder,
+    amount: u64,
+    outbid: Option<&Bidder>,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let mut account_metas = vec![
+        AccountMeta::new(bidder.key, true),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new_readonly(swap.mint_b, false),
+        AccountMeta::new(bidder.ta_b, false),
+        AccountMeta::new(bidder.bid, false),
+        AccountMeta::new(bidder.bid_vault, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+    if let Some(prev) = outbid {
+        account_metas.extend([
+            AccountMeta::new(prev.key, false),
+            AccountMeta::new(prev.ta_b, false),
+            AccountMeta::new(prev.bid, false),
+            AccountMeta::new(prev.bid_vault, false),
+        ]);
+    }
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Bid { amount }), account_metas)
+}
+
+fn setup_settle(
+    mollusk: &Mollusk,
+    swap: &Swap,
+    winner: &Bidder,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let account_metas = vec![
+        AccountMeta::new(swap.maker, false),
+        AccountMeta::new(winner.key, false),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new_readonly(swap.mint_b, false),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new(swap.vault, false),
+        AccountMeta::new(winner.ta_a, false),
+        AccountMeta::new(winner.bid, false),
+        AccountMeta::new(winner.bid_vault, false),
+        AccountMeta::new(swap.maker_ta_b, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Settle), account_metas)
+}
+
+// places a bid that has to succeed and carries its accounts over
+fn place_bid(mollusk: &Mollusk, swap: &mut Swap, bidder: &Bidder, amount: u64, outbid: Option<&Bidder>) {
+    let (instruction, accounts) = setup_bid(mollusk, swap, bidder, amount, outbid);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+    swap.apply(&result.resulting_accounts);
+}
+
+#[test]
+fn bid_locks_first_bid(){
+    let mollusk = setup_mollusk();
+    let mut swap = english_auction(&mollusk);
+    let bidder = add_bidder(&mollusk, &mut swap);
+
+    let (instruction, accounts) = setup_bid(&mollusk, &swap, &bidder, RECEIVE, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&bidder.bid_vault).unwrap()), RECEIVE);
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    let escrow_data = Escrow::load(escrow_account.data()).unwrap();
+    assert_eq!(escrow_data.english.highest_bidder, bidder.key);
+    assert_eq!(escrow_data.english.highest_bid, RECEIVE);
+}
+
+#[test]
+fn bid_rejects_below_reserve(){
+    let mollusk = setup_mollusk();
+    let mut swap = english_auction(&mollusk);
+    let bidder = add_bidder(&mollusk, &mut swap);
+
+    let (instruction, accounts) = setup_bid(&mollusk, &swap, &bidder, RECEIVE - 1, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn bid_rejects_after_deadline(){
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = END_TS;
+    let mut swap = english_auction(&mollusk);
+    let bidder = add_bidder(&mollusk, &mut swap);
+
+    let (instruction, accounts) = setup_bid(&mollusk, &swap, &bidder, RECEIVE, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn bid_refunds_outbid_bidder(){
+    let mollusk = setup_mollusk();
+    let mut swap = english_auction(&mollusk);
+    let first = add_bidder(&mollusk, &mut swap);
+    let second = add_bidder(&mollusk, &mut swap);
+    place_bid(&mollusk, &mut swap, &first, RECEIVE, None);
+
+    let (instruction, accounts) = setup_bid(&mollusk, &swap, &second, RECEIVE + 100, Some(&first));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&first.ta_b).unwrap()), BALANCE);
+    assert_eq!(result.get_account(&first.bid).unwrap().lamports(), 0);
+    assert_eq!(token_amount(result.get_account(&second.bid_vault).unwrap()), RECEIVE + 100);
+}
+
+#[test]
+fn bid_rejects_outbidding_without_beating_highest_bid(){
+    let mollusk = setup_mollusk();
+    let mut swap = english_auction(&mollusk);
+    let first = add_bidder(&mollusk, &mut swap);
+    let second = add_bidder(&mollusk, &mut swap);
+    place_bid(&mollusk, &mut swap, &first, RECEIVE, None);
+
+    let (instruction, accounts) = setup_bid(&mollusk, &swap, &second, RECEIVE, Some(&first));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn bid_raises_own_bid(){
+    let mollusk = setup_mollusk();
+    let mut swap = english_auction(&mollusk);
+    let bidder = add_bidder(&mollusk, &mut swap);
+    place_bid(&mollusk, &mut swap, &bidder, RECEIVE, None);
+
+    let (instruction, accounts) = setup_bid(&mollusk, &swap, &bidder, RECEIVE + 200, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    // the first bid came back, the raised one is locked in the same bid vault
+    assert_eq!(token_amount(result.get_account(&bidder.ta_b).unwrap()), BALANCE - RECEIVE - 200);
+    assert_eq!(token_amount(result.get_account(&bidder.bid_vault).unwrap()), RECEIVE + 200);
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().english.highest_bid, RECEIVE + 200);
+}
+
+#[test]
+fn settle_swaps_vault_for_winning_bid(){
+    let mut mollusk = setup_mollusk();
+    let mut swap = english_auction(&mollusk);
+    let bidder = add_bidder(&mollusk, &mut swap);
+    place_bid(&mollusk, &mut swap, &bidder, RECEIVE, None);
+
+    mollusk.sysvars.clock.unix_timestamp = END_TS;
+    let (instruction, accounts) = setup_settle(&mollusk, &swap, &bidder);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&bidder.ta_a).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), RECEIVE);
+    assert_eq!(result.get_account(&swap.escrow).unwrap().lamports(), 0);
+}
+
+#[test]
+fn settle_rejects_before_deadline(){
+    let mollusk = setup_mollusk();
+    let mut swap = english_auction(&mollusk);
+    let bidder = add_bidder(&mollusk, &mut swap);
+    place_bid(&mollusk, &mut swap, &bidder, RECEIVE, None);
+
+    let (instruction, accounts) = setup_settle(&mollusk, &swap, &bidder);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
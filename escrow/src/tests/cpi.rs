@@ -0,0 +1,115 @@
+use mollusk_svm::program;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::tests::{
+    setup_mollusk,
+    create_mint_account,
+    create_token_account,
+    token_program,
+    PROGRAM_ID,
+};
+
+// tests/caller, build.rs builds it into OUT_DIR with cargo build-sbf
+const CALLER_ID: SolanaPubkey = SolanaPubkey::new_from_array(five8_const::decode_32_const(
+    "33333333333333333333333333333333333333333333",
+));
+
+// borsh encoding of the caller's CallerInstruction
+fn caller_make(amount: u64, receive: u64, escrow_bump: u8) -> Vec<u8> {
+    [
+        vec![0],
+        amount.to_le_bytes().to_vec(),
+        receive.to_le_bytes().to_vec(),
+        vec![escrow_bump],
+    ]
+    .concat()
+}
+
+const CALLER_REFUND: [u8; 1] = [1];
+
+#[test]
+fn pda_maker_makes_and_refunds_through_cpi(){
+    let mut mollusk = setup_mollusk();
+    mollusk.add_program(
+        &CALLER_ID,
+        concat!(env!("OUT_DIR"), "/escrow_caller"),
+        &mollusk_svm::program::loader_keys::LOADER_V3,
+    );
+
+    // the maker is a PDA of the calling program, it only signs through invoke_signed
+    let (maker, _) = SolanaPubkey::find_program_address(&[b"maker"], &CALLER_ID);
+    let maker_account = AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID);
+
+    let mint_a = SolanaPubkey::new_unique();
+    let mint_a_account = create_mint_account(&mollusk, 1_000_000, 6);
+    let mint_b = SolanaPubkey::new_unique();
+    let mint_b_account = create_mint_account(&mollusk, 1_000_000, 6);
+
+    let (escrow, escrow_bump) = SolanaPubkey::find_program_address(&[b"escrow", maker.as_ref()], &PROGRAM_ID);
+
+    let maker_ta_a = SolanaPubkey::new_unique();
+    let maker_ta_a_account = create_token_account(maker, &mollusk, mint_a, 1_000);
+
+    // the vault is owned by the escrow PDA, created by the client beforehand
+    let vault = SolanaPubkey::new_unique();
+    let vault_account = create_token_account(escrow, &mollusk, mint_a, 0);
+
+    let (token_program, token_program_account) = token_program();
+    let (system_program, system_program_account) = program::keyed_account_for_system_program();
+
+    let account_metas = vec![
+        AccountMeta::new_readonly(PROGRAM_ID, false),
+        AccountMeta::new(maker, false),
+        AccountMeta::new_readonly(mint_a, false),
+        AccountMeta::new_readonly(mint_b, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new(maker_ta_a, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(token_program, false),
+        AccountMeta::new_readonly(system_program, false),
+    ];
+
+    let make = Instruction::new_with_bytes(CALLER_ID, &caller_make(1_000, 500, escrow_bump), account_metas.clone());
+
+    let result = mollusk.process_instruction(
+        &make,
+        &[
+            (PROGRAM_ID, program::create_program_account_loader_v3(&PROGRAM_ID)),
+            (maker, maker_account),
+            (mint_a, mint_a_account),
+            (mint_b, mint_b_account),
+            (escrow, AccountSharedData::default()),
+            (maker_ta_a, maker_ta_a_account),
+            (vault, vault_account),
+            (token_program, token_program_account),
+            (system_program, system_program_account),
+        ],
+    );
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&escrow).expect("Failed to get escrow account");
+    assert_eq!(escrow_account.owner(), &PROGRAM_ID);
+    let escrow_data = crate::state::Escrow::load(escrow_account.data()).unwrap();
+    assert_eq!(escrow_data.maker.to_bytes(), maker.to_bytes());
+    assert_eq!(escrow_data.receive, 500);
+
+    let vault_account = result.get_account(&vault).expect("Failed to get vault account");
+    assert_eq!(spl_token::state::Account::unpack(vault_account.data()).unwrap().amount, 1_000);
+
+    // the same PDA refunds, again only through the caller
+    let refund = Instruction::new_with_bytes(CALLER_ID, &CALLER_REFUND, account_metas);
+
+    let result = mollusk.process_instruction(&refund, &result.resulting_accounts);
+    assert!(!result.program_result.is_err());
+
+    let maker_ta_a_account = result.get_account(&maker_ta_a).expect("Failed to get maker token account");
+    assert_eq!(spl_token::state::Account::unpack(maker_ta_a_account.data()).unwrap().amount, 1_000);
+
+    let escrow_account = result.get_account(&escrow).expect("Failed to get escrow account");
+    assert_eq!(escrow_account.lamports(), 0);
+}
@@ -0,0 +1,168 @@
+use mollusk_svm::Mollusk;
+use solana_sdk::account::ReadableAccount;
+
+use crate::{
+    state::{DutchAuction, EscrowKind},
+    tests::{
+        setup_mollusk,
+        create_token_account,
+        token_amount,
+        Swap,
+        AMOUNT,
+    },
+};
+
+const AUCTION: DutchAuction = DutchAuction {
+    start_receive: 1_000,
+    end_receive: 200,
+    start_ts: 100,
+    end_ts: 500,
+};
+
+#[test]
+fn price_before_start(){
+    assert_eq!(AUCTION.price(i64::MIN), 1_000);
+    assert_eq!(AUCTION.price(0), 1_000);
+    assert_eq!(AUCTION.price(99), 1_000);
+}
+
+#[test]
+fn price_at_start(){
+    assert_eq!(AUCTION.price(100), 1_000);
+}
+
+#[test]
+fn price_decays_linearly(){
+    // 800 over 400 seconds, 2 per second
+    assert_eq!(AUCTION.price(101), 998);
+    assert_eq!(AUCTION.price(200), 800);
+    assert_eq!(AUCTION.price(300), 600);
+    assert_eq!(AUCTION.price(400), 400);
+    assert_eq!(AUCTION.price(499), 202);
+}
+
+#[test]
+fn price_never_increases(){
+    let mut last = AUCTION.price(AUCTION.start_ts);
+    for now in AUCTION.start_ts..=AUCTION.end_ts {
+        let price = AUCTION.price(now);
+        assert!(price <= last);
+        assert!((AUCTION.end_receive..=AUCTION.start_receive).contains(&price));
+        last = price;
+    }
+}
+
+#[test]
+fn price_at_and_after_end(){
+    assert_eq!(AUCTION.price(500), 200);
+    assert_eq!(AUCTION.price(501), 200);
+    assert_eq!(AUCTION.price(i64::MAX), 200);
+}
+
+#[test]
+fn price_rounds_in_favour_of_the_maker(){
+    // 1 over 3 seconds never reaches the end price before end_ts
+    let auction = DutchAuction {
+        start_receive: 2,
+        end_receive: 1,
+        start_ts: 0,
+        end_ts: 3,
+    };
+    assert_eq!(auction.price(1), 2);
+    assert_eq!(auction.price(2), 2);
+    assert_eq!(auction.price(3), 1);
+}
+
+#[test]
+fn price_with_large_amounts(){
+    let auction = DutchAuction {
+        start_receive: u64::MAX,
+        end_receive: 0,
+        start_ts: i64::MIN,
+        end_ts: i64::MAX,
+    };
+    assert_eq!(auction.price(i64::MIN), u64::MAX);
+    assert_eq!(auction.price(0), u64::MAX / 2);
+    assert_eq!(auction.price(i64::MAX), 0);
+}
+
+#[test]
+fn invalid_auctions(){
+    assert!(AUCTION.is_valid());
+    assert!(!DutchAuction { end_ts: AUCTION.start_ts, ..AUCTION }.is_valid());
+    assert!(!DutchAuction { end_receive: AUCTION.start_receive + 1, ..AUCTION }.is_valid());
+}
+
+// a dutch offer of AMOUNT mint a on AUCTION, the taker holds the start price in mint b
+fn dutch_swap(mollusk: &Mollusk) -> Swap {
+    let mut swap = Swap::new(mollusk);
+    swap.escrow_data.kind = EscrowKind::Dutch as u8;
+    swap.escrow_data.dutch = AUCTION;
+    swap.escrow_data.receive = AUCTION.start_receive;
+
+    let taker_ta_b = swap.taker_ta_b;
+    swap.set_account(taker_ta_b, create_token_account(swap.taker, mollusk, swap.mint_b, AUCTION.start_receive));
+
+    swap
+}
+
+// takes the dutch offer at `now` and checks the taker paid `price`
+fn take_dutch_at(now: i64, price: u64) {
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = now;
+    let swap = dutch_swap(&mollusk);
+
+    let (instruction, accounts) = swap.take(&mollusk, price, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), price);
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_b).unwrap()), AUCTION.start_receive - price);
+    assert_eq!(result.get_account(&swap.escrow).unwrap().lamports(), 0);
+}
+
+#[test]
+fn take_dutch_before_decay(){
+    take_dutch_at(AUCTION.start_ts - 1, 1_000);
+}
+
+#[test]
+fn take_dutch_during_decay(){
+    take_dutch_at(300, 600);
+}
+
+#[test]
+fn take_dutch_after_decay(){
+    take_dutch_at(AUCTION.end_ts + 1, 200);
+}
+
+#[test]
+fn take_dutch_rejects_price_above_max_receive(){
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 300;
+    let swap = dutch_swap(&mollusk);
+
+    // the price is 600 by now
+    let (instruction, accounts) = swap.take(&mollusk, 599, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+
+    // nothing moved
+    let taker_ta_b = accounts.iter().find(|(key, _)| key == &swap.taker_ta_b).map(|(_, account)| account);
+    assert_eq!(result.get_account(&swap.taker_ta_b), taker_ta_b);
+}
+
+#[test]
+fn take_dutch_accepts_max_receive_above_price(){
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 300;
+    let swap = dutch_swap(&mollusk);
+
+    // max_receive only bounds the price, the taker still pays the decayed one
+    let (instruction, accounts) = swap.take(&mollusk, AUCTION.start_receive, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), 600);
+}
@@ -0,0 +1,183 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::events::{
+    BasketLeg,
+    Event,
+    EscrowBid,
+    EscrowClaimed,
+    EscrowDisputed,
+    EscrowEvent,
+    EscrowMade,
+    EscrowRefunded,
+    EscrowReleased,
+    EscrowResolved,
+    EscrowSettled,
+    EscrowTaken,
+    EscrowVestingMade,
+};
+
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn basket() -> Vec<BasketLeg> {
+    vec![
+        BasketLeg { mint: key(10), amount: 7 },
+        BasketLeg { mint: key(11), amount: 8 },
+    ]
+}
+
+// decodes the payload `emit` logs for `event`
+fn round_trip(event: &impl Event) -> EscrowEvent {
+    EscrowEvent::decode(&event.data().unwrap()).unwrap()
+}
+
+#[test]
+fn made_round_trips(){
+    let event = EscrowMade {
+        escrow: key(1),
+        maker: key(2),
+        authority: key(3),
+        mint_a: key(4),
+        mint_b: key(5),
+        amount: 1_000,
+        receive: 500,
+        basket: basket(),
+        nft: true,
+        collection: Some(key(6)),
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Made(event));
+}
+
+#[test]
+fn taken_round_trips(){
+    let event = EscrowTaken {
+        escrow: key(1),
+        maker: key(2),
+        taker: key(3),
+        delegate: Some(key(4)),
+        mint_a: key(5),
+        mint_b: key(6),
+        amount: 1_000,
+        receive: 500,
+        basket: basket(),
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Taken(event));
+}
+
+#[test]
+fn refunded_round_trips(){
+    let event = EscrowRefunded {
+        escrow: key(1),
+        maker: key(2),
+        mint_a: key(3),
+        amount: 1_000,
+        basket: basket(),
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Refunded(event));
+}
+
+#[test]
+fn bid_round_trips(){
+    let event = EscrowBid {
+        escrow: key(1),
+        bidder: key(2),
+        amount: 600,
+        outbid: Some(key(3)),
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Bid(event));
+}
+
+#[test]
+fn settled_round_trips(){
+    let event = EscrowSettled {
+        escrow: key(1),
+        maker: key(2),
+        winner: key(3),
+        mint_a: key(4),
+        mint_b: key(5),
+        amount: 1_000,
+        receive: 600,
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Settled(event));
+}
+
+#[test]
+fn released_round_trips(){
+    let event = EscrowReleased {
+        escrow: key(1),
+        maker: key(2),
+        beneficiary: key(3),
+        mint_a: key(4),
+        amount: 1_000,
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Released(event));
+}
+
+#[test]
+fn disputed_round_trips(){
+    let event = EscrowDisputed {
+        escrow: key(1),
+        disputer: key(2),
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Disputed(event));
+}
+
+#[test]
+fn resolved_round_trips(){
+    let event = EscrowResolved {
+        escrow: key(1),
+        arbiter: key(2),
+        recipient: key(3),
+        mint_a: key(4),
+        amount: 1_000,
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Resolved(event));
+}
+
+#[test]
+fn vesting_made_round_trips(){
+    let event = EscrowVestingMade {
+        escrow: key(1),
+        maker: key(2),
+        beneficiary: key(3),
+        mint_a: key(4),
+        amount: 1_000,
+        start_ts: 100,
+        cliff_ts: 200,
+        end_ts: 300,
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::VestingMade(event));
+}
+
+#[test]
+fn claimed_round_trips(){
+    let event = EscrowClaimed {
+        escrow: key(1),
+        beneficiary: key(2),
+        amount: 250,
+        claimed: 500,
+        total: 1_000,
+    };
+    assert_eq!(round_trip(&event), EscrowEvent::Claimed(event));
+}
+
+#[test]
+fn decode_rejects_unknown_discriminator(){
+    let mut data = EscrowRefunded { escrow: key(1), maker: key(2), mint_a: key(3), amount: 1_000, basket: vec![] }.data().unwrap();
+    data[..8].copy_from_slice(b"unknown\0");
+    assert!(EscrowEvent::decode(&data).is_err());
+}
+
+#[test]
+fn decode_rejects_truncated_payload(){
+    let data = EscrowRefunded {
+        escrow: key(1),
+        maker: key(2),
+        mint_a: key(3),
+        amount: 1_000,
+        basket: basket(),
+    }.data().unwrap();
+    assert!(EscrowEvent::decode(&data[..data.len() - 1]).is_err());
+    assert!(EscrowEvent::decode(&data[..4]).is_err());
+}
@@ -0,0 +1,191 @@
+use bytemuck::Zeroable;
+use mollusk_svm::Mollusk;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::{
+    processor::{EscrowArgs, EscrowInstruction, EscrowTerms},
+    state::{OrderBook, OrderBookEntry},
+    tests::{
+        setup_mollusk,
+        create_token_account,
+        instruction_data,
+        token_amount,
+        Swap,
+        AMOUNT,
+        PROGRAM_ID,
+        RECEIVE,
+    },
+};
+
+// the index PDA of the pair of `swap`
+fn index_address(swap: &Swap) -> (SolanaPubkey, u8) {
+    SolanaPubkey::find_program_address(&[b"index", swap.mint_a.as_ref(), swap.mint_b.as_ref()], &PROGRAM_ID)
+}
+
+// an index of the pair of `swap` holding `entries`
+fn create_index_account(mollusk: &Mollusk, swap: &Swap, entries: &[OrderBookEntry]) -> AccountSharedData {
+    let mut order_book = OrderBook::zeroed();
+    order_book.discriminator = OrderBook::DISCRIMINATOR;
+    order_book.version = OrderBook::VERSION;
+    order_book.bump = index_address(swap).1;
+    order_book.len = entries.len() as u32;
+    order_book.mint_a = swap.mint_a;
+    order_book.mint_b = swap.mint_b;
+
+    let space = OrderBook::space(entries.len());
+    let mut index_account = AccountSharedData::new(mollusk.sysvars.rent.minimum_balance(space), space, &PROGRAM_ID);
+    let (header, tail) = index_account.data_as_mut_slice().split_at_mut(OrderBook::LEN);
+    header.copy_from_slice(bytemuck::bytes_of(&order_book));
+    tail.copy_from_slice(bytemuck::cast_slice(entries));
+
+    index_account
+}
+
+// a full index, every offer asks between RECEIVE and RECEIVE + MAX_ENTRIES - 1 for AMOUNT
+fn full_index() -> Vec<OrderBookEntry> {
+    (0..OrderBook::MAX_ENTRIES as u64)
+        .map(|i| OrderBookEntry {
+            escrow: SolanaPubkey::new_unique(),
+            amount: AMOUNT,
+            receive: RECEIVE + i,
+        })
+        .collect()
+}
+
+fn entries(account: &AccountSharedData) -> Vec<OrderBookEntry> {
+    OrderBook::entries(account.data()).unwrap().to_vec()
+}
+
+// make listing the offer of `swap` for `receive`
+fn setup_indexed_make(
+    mollusk: &Mollusk,
+    swap: &mut Swap,
+    receive: u64,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let (index, _) = index_address(swap);
+    swap.set_account(swap.escrow, AccountSharedData::default());
+    swap.set_account(swap.maker_ta_a, create_token_account(swap.maker, mollusk, swap.mint_a, AMOUNT));
+    swap.set_account(swap.vault, create_token_account(swap.escrow, mollusk, swap.mint_a, 0));
+
+    let args = EscrowArgs {
+        maker: swap.maker,
+        amount: AMOUNT,
+        receive,
+        escrow_bump: swap.escrow_data.bump,
+        basket_amounts: vec![],
+        terms: EscrowTerms::Fixed,
+        authority: None,
+        nft: None,
+        index: true,
+    };
+    let account_metas = vec![
+        AccountMeta::new(swap.maker, true),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new_readonly(swap.mint_b, false),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new(swap.maker_ta_a, false),
+        AccountMeta::new(swap.vault, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        AccountMeta::new(index, false),
+    ];
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Make(args)), account_metas)
+}
+
+#[test]
+fn make_creates_index_with_offer(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (index, _) = index_address(&swap);
+    swap.set_account(index, AccountSharedData::default());
+
+    let (instruction, accounts) = setup_indexed_make(&mollusk, &mut swap, RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let index_account = result.get_account(&index).expect("Failed to get index account");
+    assert_eq!(index_account.owner(), &PROGRAM_ID);
+    assert_eq!(entries(index_account), vec![OrderBookEntry { escrow: swap.escrow, amount: AMOUNT, receive: RECEIVE }]);
+}
+
+#[test]
+fn make_rejects_indexed_offer_for_nothing(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (index, _) = index_address(&swap);
+    swap.set_account(index, AccountSharedData::default());
+
+    let (instruction, accounts) = setup_indexed_make(&mollusk, &mut swap, 0);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn full_index_evicts_worst_offer_for_better_one(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (index, _) = index_address(&swap);
+    let listed = full_index();
+    let worst = listed[OrderBook::MAX_ENTRIES - 1];
+    swap.set_account(index, create_index_account(&mollusk, &swap, &listed));
+
+    let (instruction, accounts) = setup_indexed_make(&mollusk, &mut swap, RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let entries = entries(result.get_account(&index).expect("Failed to get index account"));
+    assert_eq!(entries.len(), OrderBook::MAX_ENTRIES);
+    assert!(!entries.contains(&worst));
+    assert!(entries.iter().any(|entry| entry.escrow == swap.escrow));
+}
+
+#[test]
+fn full_index_rejects_offer_not_better_than_worst(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (index, _) = index_address(&swap);
+    swap.set_account(index, create_index_account(&mollusk, &swap, &full_index()));
+
+    let (instruction, accounts) = setup_indexed_make(&mollusk, &mut swap, RECEIVE + OrderBook::MAX_ENTRIES as u64);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn take_removes_offer_from_index(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (index, _) = index_address(&swap);
+    let mut listed = full_index()[..2].to_vec();
+    listed.push(OrderBookEntry { escrow: swap.escrow, amount: AMOUNT, receive: RECEIVE });
+    swap.set_account(index, create_index_account(&mollusk, &swap, &listed));
+    swap.escrow_data.indexed = 1;
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![AccountMeta::new(index, false)]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(entries(result.get_account(&index).unwrap()), listed[..2].to_vec());
+}
+
+#[test]
+fn take_of_evicted_offer_leaves_index_as_is(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (index, _) = index_address(&swap);
+    let listed = full_index();
+    swap.set_account(index, create_index_account(&mollusk, &swap, &listed));
+    swap.escrow_data.indexed = 1;
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![AccountMeta::new(index, false)]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(entries(result.get_account(&index).unwrap()), listed);
+}
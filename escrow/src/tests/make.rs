@@ -0,0 +1,434 @@
+use mollusk_svm::Mollusk;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::{
+    metadata::{metadata_address, METADATA_PROGRAM_ID},
+    processor::{EscrowArgs, EscrowInstruction, EscrowTerms, NftArgs},
+    state::{Escrow, EscrowKind},
+    tests::{
+        metadata::metadata,
+        setup_mollusk,
+        create_mint_account,
+        create_multisig_account,
+        create_token_account,
+        instruction_data,
+        token_amount,
+        Swap,
+        AMOUNT,
+        PROGRAM_ID,
+        RECEIVE,
+    },
+};
+
+// a fixed price offer of AMOUNT mint a for RECEIVE mint b at the canonical escrow PDA
+fn fixed_args(swap: &Swap) -> EscrowArgs {
+    EscrowArgs {
+        maker: swap.maker,
+        amount: AMOUNT,
+        receive: RECEIVE,
+        escrow_bump: swap.escrow_data.bump,
+        basket_amounts: vec![],
+        terms: EscrowTerms::Fixed,
+        authority: None,
+        nft: None,
+        index: false,
+    }
+}
+
+// the accounts of `swap` before make, the maker still holds AMOUNT and the escrow doesn't exist
+fn setup_make(
+    mollusk: &Mollusk,
+    swap: &mut Swap,
+    args: EscrowArgs,
+    remaining: Vec<AccountMeta>,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    swap.set_account(swap.escrow, AccountSharedData::default());
+    swap.set_account(swap.maker_ta_a, create_token_account(swap.maker, mollusk, swap.mint_a, AMOUNT));
+    swap.set_account(swap.vault, create_token_account(swap.escrow, mollusk, swap.mint_a, 0));
+
+    let mut account_metas = vec![
+        AccountMeta::new(swap.maker, true),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new_readonly(swap.mint_b, false),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new(swap.maker_ta_a, false),
+        AccountMeta::new(swap.vault, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+    account_metas.extend(remaining);
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Make(args)), account_metas)
+}
+
+#[test]
+fn make_writes_discriminator_and_version(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = fixed_args(&swap);
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(escrow_account.owner(), &PROGRAM_ID);
+    assert_eq!(&escrow_account.data()[..8], &Escrow::DISCRIMINATOR);
+    let escrow_data = Escrow::load(escrow_account.data()).unwrap();
+    assert_eq!(escrow_data.version, Escrow::VERSION);
+    assert_eq!(escrow_data.receive, RECEIVE);
+
+    assert_eq!(token_amount(result.get_account(&swap.vault).unwrap()), AMOUNT);
+}
+
+#[test]
+fn make_stores_canonical_bump(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = fixed_args(&swap);
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let (_, escrow_bump) = SolanaPubkey::find_program_address(&[b"escrow", swap.maker.as_ref()], &PROGRAM_ID);
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().bump, escrow_bump);
+}
+
+#[test]
+fn make_rejects_non_canonical_bump(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let mut args = fixed_args(&swap);
+    args.escrow_bump = args.escrow_bump.wrapping_sub(1);
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_deposits_basket_legs(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let leg = swap.add_basket_leg(&mollusk, 0);
+    swap.set_account(leg.maker_ta, create_token_account(swap.maker, &mollusk, leg.mint, 300));
+
+    let mut args = fixed_args(&swap);
+    args.basket_amounts = vec![300];
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, leg.metas(leg.maker_ta));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().extra_mints, 1);
+    assert_eq!(Escrow::basket(escrow_account.data()).unwrap(), &[leg.mint]);
+
+    assert_eq!(token_amount(result.get_account(&swap.vault).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&leg.vault).unwrap()), 300);
+}
+
+#[test]
+fn make_rejects_mint_a_in_basket(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let leg = swap.add_basket_leg(&mollusk, 0);
+
+    let mut args = fixed_args(&swap);
+    args.basket_amounts = vec![300];
+
+    // mint a can't be deposited a second time as a basket leg
+    let remaining = vec![
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new(swap.maker_ta_a, false),
+        AccountMeta::new(leg.vault, false),
+    ];
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_records_multisig_authority(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let multisig = SolanaPubkey::new_unique();
+    let signers = [SolanaPubkey::new_unique(), SolanaPubkey::new_unique()];
+    swap.set_account(multisig, create_multisig_account(&mollusk, 2, &signers));
+    for signer in signers {
+        swap.set_account(signer, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+    }
+
+    let mut args = fixed_args(&swap);
+    args.authority = Some(multisig);
+    let remaining = vec![
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new_readonly(signers[0], true),
+        AccountMeta::new_readonly(signers[1], true),
+    ];
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().authority, multisig);
+}
+
+#[test]
+fn make_rejects_multisig_below_threshold(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let multisig = SolanaPubkey::new_unique();
+    let signers = [SolanaPubkey::new_unique(), SolanaPubkey::new_unique()];
+    swap.set_account(multisig, create_multisig_account(&mollusk, 2, &signers));
+    for signer in signers {
+        swap.set_account(signer, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+    }
+
+    let mut args = fixed_args(&swap);
+    args.authority = Some(multisig);
+    let remaining = vec![
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new_readonly(signers[0], true),
+        AccountMeta::new_readonly(signers[1], false),
+    ];
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_collection_bid_stores_collection(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let collection = SolanaPubkey::new_unique();
+
+    // any NFT of the collection is taken, there is no mint b
+    swap.mint_b = SolanaPubkey::default();
+    let mut args = fixed_args(&swap);
+    args.receive = 1;
+    args.terms = EscrowTerms::CollectionBid { collection };
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    let escrow_data = Escrow::load(escrow_account.data()).unwrap();
+    assert_eq!(escrow_data.kind, EscrowKind::CollectionBid as u8);
+    assert_eq!(escrow_data.collection, collection);
+}
+
+#[test]
+fn make_collection_bid_rejects_mint_b(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    let mut args = fixed_args(&swap);
+    args.receive = 1;
+    args.terms = EscrowTerms::CollectionBid { collection: SolanaPubkey::new_unique() };
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_arbitrated_stores_parties(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let beneficiary = SolanaPubkey::new_unique();
+    let arbiter = SolanaPubkey::new_unique();
+
+    let mut args = fixed_args(&swap);
+    args.terms = EscrowTerms::Arbitrated { beneficiary, arbiter };
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    let escrow_data = Escrow::load(escrow_account.data()).unwrap();
+    assert_eq!(escrow_data.kind, EscrowKind::Arbitrated as u8);
+    assert_eq!(escrow_data.arbitration.beneficiary, beneficiary);
+    assert_eq!(escrow_data.arbitration.arbiter, arbiter);
+}
+
+#[test]
+fn make_arbitrated_rejects_maker_as_arbiter(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    let mut args = fixed_args(&swap);
+    args.terms = EscrowTerms::Arbitrated { beneficiary: SolanaPubkey::new_unique(), arbiter: swap.maker };
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_arbitrated_rejects_beneficiary_as_arbiter(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let beneficiary = SolanaPubkey::new_unique();
+
+    let mut args = fixed_args(&swap);
+    args.terms = EscrowTerms::Arbitrated { beneficiary, arbiter: beneficiary };
+
+    let (instruction, accounts) = setup_make(&mollusk, &mut swap, args, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+// an NFT offer of mint a, a mint of `supply` with `decimals` of which the maker holds one token,
+// its metadata account records `metadata_collection` and is passed when `args` names a collection
+fn setup_nft_make(
+    mollusk: &Mollusk,
+    swap: &mut Swap,
+    args: EscrowArgs,
+    supply: u64,
+    decimals: u8,
+    metadata_collection: Option<(bool, SolanaPubkey)>,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    swap.set_account(swap.mint_a, create_mint_account(mollusk, supply, decimals));
+
+    let nft_metadata = metadata_address(&swap.mint_a);
+    let mut metadata_account = AccountSharedData::new(1_000_000_000, 0, &METADATA_PROGRAM_ID);
+    metadata_account.set_data_from_slice(&metadata(&swap.mint_a, 0, metadata_collection));
+    swap.set_account(nft_metadata, metadata_account);
+
+    let remaining = match args.nft {
+        Some(NftArgs { collection: Some(_) }) => vec![AccountMeta::new_readonly(nft_metadata, false)],
+        _ => vec![],
+    };
+    let (instruction, mut accounts) = setup_make(mollusk, swap, args, remaining);
+
+    let maker_ta_a = create_token_account(swap.maker, mollusk, swap.mint_a, 1);
+    for (key, account) in accounts.iter_mut() {
+        if *key == swap.maker_ta_a {
+            *account = maker_ta_a.clone();
+        }
+    }
+    (instruction, accounts)
+}
+
+fn nft_args(swap: &Swap, collection: Option<SolanaPubkey>) -> EscrowArgs {
+    EscrowArgs {
+        amount: 1,
+        nft: Some(NftArgs { collection }),
+        ..fixed_args(swap)
+    }
+}
+
+#[test]
+fn make_nft_with_verified_collection(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let collection = SolanaPubkey::new_unique();
+    let args = nft_args(&swap, Some(collection));
+
+    let (instruction, accounts) = setup_nft_make(&mollusk, &mut swap, args, 1, 0, Some((true, collection)));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    let escrow_data = Escrow::load(escrow_account.data()).unwrap();
+    assert_eq!(escrow_data.nft, 1);
+    assert_eq!(escrow_data.collection, collection);
+
+    let vault_account = result.get_account(&swap.vault).expect("Failed to get vault account");
+    assert_eq!(token_amount(vault_account), 1);
+}
+
+#[test]
+fn make_nft_rejects_mint_with_decimals(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = nft_args(&swap, None);
+
+    let (instruction, accounts) = setup_nft_make(&mollusk, &mut swap, args, 1, 6, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_nft_rejects_mint_with_supply_above_one(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = nft_args(&swap, None);
+
+    let (instruction, accounts) = setup_nft_make(&mollusk, &mut swap, args, 2, 0, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_nft_rejects_amount_other_than_one(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    // the maker could deposit nothing, only the NFT check stops it
+    let mut args = nft_args(&swap, None);
+    args.amount = 0;
+
+    let (instruction, accounts) = setup_nft_make(&mollusk, &mut swap, args, 1, 0, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_nft_rejects_missing_metadata_account(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = nft_args(&swap, Some(SolanaPubkey::new_unique()));
+
+    let (mut instruction, mut accounts) = setup_nft_make(&mollusk, &mut swap, args, 1, 0, None);
+    instruction.accounts.pop();
+    accounts.retain(|(key, _)| *key != metadata_address(&swap.mint_a));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_nft_rejects_metadata_without_collection(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = nft_args(&swap, Some(SolanaPubkey::new_unique()));
+
+    let (instruction, accounts) = setup_nft_make(&mollusk, &mut swap, args, 1, 0, None);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_nft_rejects_unverified_collection(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let collection = SolanaPubkey::new_unique();
+    let args = nft_args(&swap, Some(collection));
+
+    let (instruction, accounts) = setup_nft_make(&mollusk, &mut swap, args, 1, 0, Some((false, collection)));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn make_nft_rejects_other_collection(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = nft_args(&swap, Some(SolanaPubkey::new_unique()));
+
+    let (instruction, accounts) = setup_nft_make(&mollusk, &mut swap, args, 1, 0, Some((true, SolanaPubkey::new_unique())));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
@@ -0,0 +1,75 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::metadata::verified_collection;
+
+const MINT: Pubkey = Pubkey::new_from_array([1; 32]);
+const COLLECTION: Pubkey = Pubkey::new_from_array([2; 32]);
+
+// borsh layout of a MetadataV1 account, names padded like the metadata program does
+pub(super) fn metadata(mint: &Pubkey, creators: u32, collection: Option<(bool, Pubkey)>) -> Vec<u8> {
+    let mut data = vec![4]; // key
+    data.extend_from_slice(&[9; 32]); // update authority
+    data.extend_from_slice(mint.as_ref());
+    for len in [32u32, 10, 200] {
+        data.extend_from_slice(&len.to_le_bytes());
+        data.extend(std::iter::repeat_n(0, len as usize));
+    }
+    data.extend_from_slice(&500u16.to_le_bytes()); // seller fee basis points
+    if creators > 0 {
+        data.push(1);
+        data.extend_from_slice(&creators.to_le_bytes());
+        data.extend(std::iter::repeat_n(7, creators as usize * 34));
+    } else {
+        data.push(0);
+    }
+    data.extend_from_slice(&[1, 1]); // primary sale happened, is mutable
+    data.extend_from_slice(&[1, 255]); // edition nonce
+    data.extend_from_slice(&[1, 0]); // token standard
+    match collection {
+        Some((verified, key)) => {
+            data.push(1);
+            data.push(verified as u8);
+            data.extend_from_slice(key.as_ref());
+        }
+        None => data.push(0),
+    }
+    // uses, collection details, programmable config and the zero padding after
+    data.extend(std::iter::repeat_n(0, 100));
+    data
+}
+
+#[test]
+fn reads_verified_collection(){
+    let data = metadata(&MINT, 0, Some((true, COLLECTION)));
+    assert_eq!(verified_collection(&data, &MINT).unwrap(), Some(COLLECTION));
+}
+
+#[test]
+fn skips_creators(){
+    let data = metadata(&MINT, 3, Some((true, COLLECTION)));
+    assert_eq!(verified_collection(&data, &MINT).unwrap(), Some(COLLECTION));
+}
+
+#[test]
+fn unverified_collection_is_none(){
+    let data = metadata(&MINT, 1, Some((false, COLLECTION)));
+    assert_eq!(verified_collection(&data, &MINT).unwrap(), None);
+}
+
+#[test]
+fn no_collection_is_none(){
+    let data = metadata(&MINT, 0, None);
+    assert_eq!(verified_collection(&data, &MINT).unwrap(), None);
+}
+
+#[test]
+fn rejects_metadata_of_another_mint(){
+    let data = metadata(&MINT, 0, Some((true, COLLECTION)));
+    assert!(verified_collection(&data, &Pubkey::new_from_array([3; 32])).is_err());
+}
+
+#[test]
+fn rejects_truncated_metadata(){
+    let data = metadata(&MINT, 2, Some((true, COLLECTION)));
+    assert!(verified_collection(&data[..100], &MINT).is_err());
+}
@@ -0,0 +1,463 @@
+#![cfg(test)]
+mod arbitration;
+mod bid;
+mod cpi;
+mod dutch_auction;
+mod events;
+mod index;
+mod make;
+mod metadata;
+mod multisig;
+mod order_book;
+mod refund;
+mod take;
+mod vesting;
+
+/**
+ * Setup functions for mollusk tests
+ */
+use bytemuck::Zeroable;
+use mollusk_svm::{
+    program,
+    Mollusk,
+};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
+    instruction::{AccountMeta, Instruction},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey as SolanaPubkey,
+};
+use spl_token::state::AccountState;
+
+use crate::{
+    processor::EscrowInstruction,
+    state::{Escrow, EscrowKind, Multisig},
+};
+
+const PROGRAM_ID: SolanaPubkey = SolanaPubkey::new_from_array(five8_const::decode_32_const(
+    "22222222222222222222222222222222222222222222",
+));
+
+// the offer of `Swap`: AMOUNT of mint a in the vault for RECEIVE of mint b
+const AMOUNT: u64 = 1_000;
+const RECEIVE: u64 = 500;
+
+fn setup_mollusk() -> Mollusk {
+    let mut mollusk = Mollusk::new(&PROGRAM_ID, "target/deploy/escrow");
+
+    mollusk.add_program(
+        &spl_token::ID,
+        "src/tests/spl_token-3.5.0",
+        &mollusk_svm::program::loader_keys::LOADER_V3,
+    );
+
+    mollusk
+}
+
+/**
+ * Creates an initialized token account
+ *
+ * @param owner: The owner of the token account, the escrow PDA for vaults
+ * @param mollusk: The mollusk instance
+ * @param mint: The mint that the token account is for
+ * @param amount: The amount of the token account
+ * @returns: An initialized token account
+ */
+fn create_token_account(
+    owner: SolanaPubkey,
+    mollusk: &Mollusk,
+    mint: SolanaPubkey,
+    amount: u64,
+) -> AccountSharedData {
+    let mut token_account = AccountSharedData::new(
+        mollusk
+            .sysvars
+            .rent
+            .minimum_balance(spl_token::state::Account::LEN),
+        spl_token::state::Account::LEN,
+        &spl_token::ID,
+    );
+    spl_token::state::Account::pack(
+        spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        token_account.data_as_mut_slice(),
+    ).unwrap();
+
+    token_account
+}
+
+/**
+ * Creates an initialized mint account
+ *
+ * @param mollusk: The mollusk instance
+ * @param supply: The supply of the mint
+ * @param decimals: The decimals of the mint
+ * @returns: An initialized mint account
+ */
+fn create_mint_account(mollusk: &Mollusk, supply: u64, decimals: u8) -> AccountSharedData {
+    let mut mint_account = AccountSharedData::new(
+        mollusk
+            .sysvars
+            .rent
+            .minimum_balance(spl_token::state::Mint::LEN),
+        spl_token::state::Mint::LEN,
+        &spl_token::ID,
+    );
+    Pack::pack(
+        spl_token::state::Mint {
+            mint_authority: COption::Some(SolanaPubkey::new_from_array([0x05; 32])),
+            supply,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        mint_account.data_as_mut_slice(),
+    ).unwrap();
+
+    mint_account
+}
+
+/**
+ * The token program account for instructions that invoke it
+ *
+ * @returns: The token program id and its account
+ */
+fn token_program() -> (SolanaPubkey, AccountSharedData) {
+    (
+        spl_token::ID,
+        program::create_program_account_loader_v3(&spl_token::ID),
+    )
+}
+
+/**
+ * Serializes an escrow instruction
+ *
+ * @param instruction: The instruction to serialize
+ * @returns: The instruction data
+ */
+fn instruction_data(instruction: EscrowInstruction) -> Vec<u8> {
+    borsh::to_vec(&instruction).unwrap()
+}
+
+/**
+ * A fixed price escrow at the canonical PDA of `maker`, as make stores it
+ *
+ * @param maker: The maker of the escrow
+ * @param mint_a: The mint deposited into the vault
+ * @param mint_b: The mint the maker asks for
+ * @param receive: The amount of mint b the maker asks for
+ * @returns: The escrow PDA and its data
+ */
+fn fixed_escrow(
+    maker: SolanaPubkey,
+    mint_a: SolanaPubkey,
+    mint_b: SolanaPubkey,
+    receive: u64,
+) -> (SolanaPubkey, Escrow) {
+    let (escrow, escrow_bump) = SolanaPubkey::find_program_address(&[b"escrow", maker.as_ref()], &PROGRAM_ID);
+
+    let mut escrow_data = Escrow::zeroed();
+    escrow_data.discriminator = Escrow::DISCRIMINATOR;
+    escrow_data.version = Escrow::VERSION;
+    escrow_data.bump = escrow_bump;
+    escrow_data.kind = EscrowKind::Fixed as u8;
+    escrow_data.maker = maker;
+    escrow_data.authority = maker;
+    escrow_data.mint_a = mint_a;
+    escrow_data.mint_b = mint_b;
+    escrow_data.receive = receive;
+
+    (escrow, escrow_data)
+}
+
+/**
+ * Creates an escrow account owned by the program
+ *
+ * @param mollusk: The mollusk instance
+ * @param escrow_data: The escrow header
+ * @param basket: The basket mints stored after the header
+ * @returns: An initialized escrow account
+ */
+fn create_escrow_account(
+    mollusk: &Mollusk,
+    escrow_data: &Escrow,
+    basket: &[SolanaPubkey],
+) -> AccountSharedData {
+    let space = Escrow::space(basket.len());
+    let mut escrow_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(space),
+        space,
+        &PROGRAM_ID,
+    );
+    let (header, tail) = escrow_account.data_as_mut_slice().split_at_mut(Escrow::LEN);
+    header.copy_from_slice(bytemuck::bytes_of(escrow_data));
+    tail.copy_from_slice(bytemuck::cast_slice(basket));
+
+    escrow_account
+}
+
+/**
+ * Creates an initialized M-of-N multisig owned by the program
+ *
+ * @param mollusk: The mollusk instance
+ * @param m: The signatures required
+ * @param signers: The n listed signers
+ * @returns: An initialized multisig account
+ */
+fn create_multisig_account(mollusk: &Mollusk, m: u8, signers: &[SolanaPubkey]) -> AccountSharedData {
+    let mut multisig_data = Multisig::zeroed();
+    multisig_data.discriminator = Multisig::DISCRIMINATOR;
+    multisig_data.version = Multisig::VERSION;
+    multisig_data.m = m;
+    multisig_data.n = signers.len() as u8;
+    multisig_data.signers[..signers.len()].copy_from_slice(signers);
+
+    let mut multisig_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(Multisig::LEN),
+        Multisig::LEN,
+        &PROGRAM_ID,
+    );
+    multisig_account.data_as_mut_slice().copy_from_slice(bytemuck::bytes_of(&multisig_data));
+
+    multisig_account
+}
+
+/**
+ * Makes a 2-of-3 multisig the authority of the escrow of `swap`
+ *
+ * @param mollusk: The mollusk instance
+ * @param swap: The swap whose escrow the multisig controls
+ * @returns: The multisig and its signers
+ */
+fn multisig_authority(mollusk: &Mollusk, swap: &mut Swap) -> (SolanaPubkey, [SolanaPubkey; 3]) {
+    let multisig = SolanaPubkey::new_unique();
+    let signers = [SolanaPubkey::new_unique(), SolanaPubkey::new_unique(), SolanaPubkey::new_unique()];
+
+    swap.set_account(multisig, create_multisig_account(mollusk, 2, &signers));
+    for signer in signers {
+        swap.set_account(signer, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+    }
+    swap.escrow_data.authority = multisig;
+
+    (multisig, signers)
+}
+
+/**
+ * Accounts of a fixed price offer of AMOUNT mint a for RECEIVE mint b as make leaves it,
+ * and of a taker holding RECEIVE of mint b
+ */
+struct Swap {
+    maker: SolanaPubkey,
+    taker: SolanaPubkey,
+    mint_a: SolanaPubkey,
+    mint_b: SolanaPubkey,
+    escrow: SolanaPubkey,
+    escrow_data: Escrow, // the escrow account is built from it unless one was set, tests can change it first
+    basket: Vec<SolanaPubkey>,
+    maker_ta_a: SolanaPubkey,
+    maker_ta_b: SolanaPubkey,
+    taker_ta_a: SolanaPubkey,
+    taker_ta_b: SolanaPubkey,
+    vault: SolanaPubkey,
+    accounts: Vec<(SolanaPubkey, AccountSharedData)>, // every account above but the escrow, and the ones set by tests
+}
+
+// accounts of a basket leg of `Swap`
+struct SwapLeg {
+    mint: SolanaPubkey,
+    maker_ta: SolanaPubkey,
+    taker_ta: SolanaPubkey,
+    vault: SolanaPubkey,
+}
+
+impl SwapLeg {
+    // the remaining accounts of the leg, `user_ta` is the maker's or the taker's
+    fn metas(&self, user_ta: SolanaPubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new(user_ta, false),
+            AccountMeta::new(self.vault, false),
+        ]
+    }
+}
+
+impl Swap {
+    fn new(mollusk: &Mollusk) -> Self {
+        let maker = SolanaPubkey::new_unique();
+        let taker = SolanaPubkey::new_unique();
+        let mint_a = SolanaPubkey::new_unique();
+        let mint_b = SolanaPubkey::new_unique();
+        let (escrow, escrow_data) = fixed_escrow(maker, mint_a, mint_b, RECEIVE);
+
+        let maker_ta_a = SolanaPubkey::new_unique();
+        let maker_ta_b = SolanaPubkey::new_unique();
+        let taker_ta_a = SolanaPubkey::new_unique();
+        let taker_ta_b = SolanaPubkey::new_unique();
+        let vault = SolanaPubkey::new_unique();
+
+        let accounts = vec![
+            (maker, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID)),
+            (taker, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID)),
+            (mint_a, create_mint_account(mollusk, 1_000_000, 6)),
+            (mint_b, create_mint_account(mollusk, 1_000_000, 6)),
+            (maker_ta_a, create_token_account(maker, mollusk, mint_a, 0)),
+            (maker_ta_b, create_token_account(maker, mollusk, mint_b, 0)),
+            (taker_ta_a, create_token_account(taker, mollusk, mint_a, 0)),
+            (taker_ta_b, create_token_account(taker, mollusk, mint_b, RECEIVE)),
+            (vault, create_token_account(escrow, mollusk, mint_a, AMOUNT)),
+        ];
+
+        Self {
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            escrow_data,
+            basket: vec![],
+            maker_ta_a,
+            maker_ta_b,
+            taker_ta_a,
+            taker_ta_b,
+            vault,
+            accounts,
+        }
+    }
+
+    // adds a basket leg of `amount` deposited in its own vault
+    fn add_basket_leg(&mut self, mollusk: &Mollusk, amount: u64) -> SwapLeg {
+        let leg = SwapLeg {
+            mint: SolanaPubkey::new_unique(),
+            maker_ta: SolanaPubkey::new_unique(),
+            taker_ta: SolanaPubkey::new_unique(),
+            vault: SolanaPubkey::new_unique(),
+        };
+
+        self.set_account(leg.mint, create_mint_account(mollusk, 1_000_000, 6));
+        self.set_account(leg.maker_ta, create_token_account(self.maker, mollusk, leg.mint, 0));
+        self.set_account(leg.taker_ta, create_token_account(self.taker, mollusk, leg.mint, 0));
+        self.set_account(leg.vault, create_token_account(self.escrow, mollusk, leg.mint, amount));
+
+        self.escrow_data.extra_mints += 1;
+        self.basket.push(leg.mint);
+
+        leg
+    }
+
+    // carries the accounts an instruction left over to the next one
+    fn apply(&mut self, resulting_accounts: &[(SolanaPubkey, AccountSharedData)]) {
+        for (key, account) in resulting_accounts {
+            self.set_account(*key, account.clone());
+        }
+    }
+
+    fn account(&self, mollusk: &Mollusk, key: &SolanaPubkey) -> AccountSharedData {
+        match self.accounts.iter().find(|(account_key, _)| account_key == key) {
+            Some((_, account)) => account.clone(),
+            None if key == &self.escrow => create_escrow_account(mollusk, &self.escrow_data, &self.basket),
+            None => panic!("Unknown swap account"),
+        }
+    }
+
+    // replaces an account, or adds one for the remaining accounts
+    fn set_account(&mut self, key: SolanaPubkey, account: AccountSharedData) {
+        self.accounts.retain(|(account_key, _)| account_key != &key);
+        self.accounts.push((key, account));
+    }
+
+    fn instruction(
+        &self,
+        mollusk: &Mollusk,
+        data: Vec<u8>,
+        account_metas: Vec<AccountMeta>,
+    ) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+        let accounts = account_metas
+            .iter()
+            .map(|meta| match meta.pubkey {
+                key if key == spl_token::ID => token_program(),
+                key if key == solana_sdk::system_program::ID => program::keyed_account_for_system_program(),
+                key => (key, self.account(mollusk, &key)),
+            })
+            .collect();
+
+        (Instruction::new_with_bytes(PROGRAM_ID, &data, account_metas), accounts)
+    }
+
+    /**
+     * Take signed by the taker
+     *
+     * @param remaining: Metas of the remaining accounts, added with `set_account`
+     */
+    fn take(
+        &self,
+        mollusk: &Mollusk,
+        max_receive: u64,
+        remaining: Vec<AccountMeta>,
+    ) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+        let mut account_metas = self.take_metas(true);
+        account_metas.extend(remaining);
+
+        self.instruction(mollusk, instruction_data(EscrowInstruction::Take { max_receive }), account_metas)
+    }
+
+    // the take accounts, a delegated take passes the taker without signature
+    fn take_metas(&self, taker_signs: bool) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.taker, taker_signs),
+            AccountMeta::new(self.maker, false),
+            AccountMeta::new_readonly(self.mint_a, false),
+            AccountMeta::new_readonly(self.mint_b, false),
+            AccountMeta::new(self.escrow, false),
+            AccountMeta::new(self.maker_ta_b, false),
+            AccountMeta::new(self.taker_ta_a, false),
+            AccountMeta::new(self.taker_ta_b, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ]
+    }
+
+    /**
+     * Refund, signed by the maker if `maker_signs`
+     *
+     * @param remaining: Metas of the remaining accounts, added with `set_account`
+     */
+    fn refund(
+        &self,
+        mollusk: &Mollusk,
+        maker_signs: bool,
+        remaining: Vec<AccountMeta>,
+    ) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+        let mut account_metas = vec![
+            AccountMeta::new(self.maker, maker_signs),
+            AccountMeta::new_readonly(self.mint_a, false),
+            AccountMeta::new(self.escrow, false),
+            AccountMeta::new(self.maker_ta_a, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ];
+        account_metas.extend(remaining);
+
+        self.instruction(mollusk, instruction_data(EscrowInstruction::Refund), account_metas)
+    }
+}
+
+/**
+ * Reads the amount of a token account
+ *
+ * @param account: The token account
+ * @returns: Its amount
+ */
+fn token_amount(account: &AccountSharedData) -> u64 {
+    spl_token::state::Account::unpack(account.data()).unwrap().amount
+}
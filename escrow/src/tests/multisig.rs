@@ -0,0 +1,51 @@
+use bytemuck::Zeroable;
+use solana_program::pubkey::Pubkey;
+
+use crate::state::Multisig;
+
+fn two_of_three() -> Multisig {
+    let mut multisig = Multisig::zeroed();
+    multisig.discriminator = Multisig::DISCRIMINATOR;
+    multisig.version = Multisig::VERSION;
+    multisig.m = 2;
+    multisig.n = 3;
+    for i in 0..3 {
+        multisig.signers[i] = Pubkey::new_from_array([i as u8 + 1; 32]);
+    }
+    multisig
+}
+
+#[test]
+fn approved_by_m_signers(){
+    let multisig = two_of_three();
+    assert!(multisig.is_approved_by([multisig.signers[0], multisig.signers[2]].iter()));
+    assert!(multisig.is_approved_by(multisig.signers[..3].iter()));
+}
+
+#[test]
+fn not_approved_below_m(){
+    let multisig = two_of_three();
+    assert!(!multisig.is_approved_by([multisig.signers[1]].iter()));
+    assert!(!multisig.is_approved_by([].iter()));
+}
+
+#[test]
+fn repeated_signer_counts_once(){
+    let multisig = two_of_three();
+    assert!(!multisig.is_approved_by([multisig.signers[0], multisig.signers[0]].iter()));
+}
+
+#[test]
+fn unlisted_signers_dont_count(){
+    let multisig = two_of_three();
+    // zeroed slots past n are not signers
+    let outsider = Pubkey::new_unique();
+    assert!(!multisig.is_approved_by([multisig.signers[0], outsider, Pubkey::default()].iter()));
+}
+
+#[test]
+fn load_checks_discriminator(){
+    let multisig = two_of_three();
+    assert!(Multisig::load(bytemuck::bytes_of(&multisig)).is_ok());
+    assert!(Multisig::load(bytemuck::bytes_of(&Multisig::zeroed())).is_err());
+}
@@ -0,0 +1,66 @@
+use bytemuck::Zeroable;
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{OrderBook, OrderBookEntry};
+
+fn entry(escrow: u8, amount: u64, receive: u64) -> OrderBookEntry {
+    OrderBookEntry {
+        escrow: Pubkey::new_from_array([escrow; 32]),
+        amount,
+        receive,
+    }
+}
+
+// account data of an index holding `entries`, u64 backed to keep the entries aligned
+fn order_book(entries: &[OrderBookEntry]) -> Vec<u64> {
+    let mut header = OrderBook::zeroed();
+    header.discriminator = OrderBook::DISCRIMINATOR;
+    header.version = OrderBook::VERSION;
+    header.len = entries.len() as u32;
+
+    let mut data = vec![0u64; OrderBook::space(entries.len()) / 8];
+    let bytes = bytemuck::cast_slice_mut::<u64, u8>(&mut data);
+    bytes[..OrderBook::LEN].copy_from_slice(bytemuck::bytes_of(&header));
+    bytes[OrderBook::LEN..].copy_from_slice(bytemuck::cast_slice(entries));
+    data
+}
+
+#[test]
+fn reads_entries(){
+    let entries = [entry(1, 100, 50), entry(2, 10, 10)];
+    let data = order_book(&entries);
+    assert_eq!(OrderBook::entries(bytemuck::cast_slice(&data)).unwrap(), &entries);
+}
+
+#[test]
+fn best_offer_is_lowest_price_per_unit(){
+    // 0.5, 1 and 0.25 mint b per mint a
+    let data = order_book(&[entry(1, 100, 50), entry(2, 10, 10), entry(3, 400, 100)]);
+    assert_eq!(OrderBook::best_offer(bytemuck::cast_slice(&data)).unwrap(), Some(&entry(3, 400, 100)));
+}
+
+#[test]
+fn best_offer_of_huge_amounts(){
+    let data = order_book(&[entry(1, u64::MAX, u64::MAX), entry(2, u64::MAX, u64::MAX - 1)]);
+    assert_eq!(OrderBook::best_offer(bytemuck::cast_slice(&data)).unwrap(), Some(&entry(2, u64::MAX, u64::MAX - 1)));
+}
+
+#[test]
+fn empty_order_book_has_no_best_offer(){
+    let data = order_book(&[]);
+    assert_eq!(OrderBook::best_offer(bytemuck::cast_slice(&data)).unwrap(), None);
+}
+
+#[test]
+fn load_checks_length(){
+    let data = order_book(&[entry(1, 100, 50)]);
+    let bytes = bytemuck::cast_slice::<u64, u8>(&data);
+    assert!(OrderBook::load(bytes).is_ok());
+    assert!(OrderBook::load(&bytes[..OrderBook::space(0)]).is_err());
+}
+
+#[test]
+fn worst_offer_is_highest_price_per_unit(){
+    let data = order_book(&[entry(1, 100, 50), entry(2, 10, 10), entry(3, 400, 100)]);
+    assert_eq!(OrderBook::worst_offer(bytemuck::cast_slice(&data)).unwrap(), Some((1, &entry(2, 10, 10))));
+}
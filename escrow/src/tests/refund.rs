@@ -0,0 +1,149 @@
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    instruction::AccountMeta,
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::tests::{
+    setup_mollusk,
+    create_multisig_account,
+    multisig_authority,
+    token_amount,
+    Swap,
+    AMOUNT,
+};
+
+#[test]
+fn refund_rejects_account_without_escrow_discriminator(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    // same layout, but not written by make
+    swap.escrow_data.discriminator = *b"bid\0\0\0\0\0";
+
+    let (instruction, accounts) = swap.refund(&mollusk, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn refund_returns_whole_vault_balance(){
+    let mollusk = setup_mollusk();
+    let swap = Swap::new(&mollusk);
+
+    // the vault holds AMOUNT of mint a, receive is RECEIVE of mint b
+    let (instruction, accounts) = swap.refund(&mollusk, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(result.get_account(&swap.escrow).unwrap().lamports(), 0);
+}
+
+#[test]
+fn refund_returns_basket_legs(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let leg = swap.add_basket_leg(&mollusk, 300);
+
+    let (instruction, accounts) = swap.refund(&mollusk, true, leg.metas(leg.maker_ta));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&leg.maker_ta).unwrap()), 300);
+}
+
+#[test]
+fn refund_rejects_basket_vault_of_another_mint(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let leg = swap.add_basket_leg(&mollusk, 300);
+
+    // the main vault passed in as the basket leg's vault
+    let remaining = vec![
+        AccountMeta::new_readonly(leg.mint, false),
+        AccountMeta::new(leg.maker_ta, false),
+        AccountMeta::new(swap.vault, false),
+    ];
+
+    let (instruction, accounts) = swap.refund(&mollusk, true, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+// the multisig followed by its signers, each paired with whether it signs
+fn authority_metas(multisig: SolanaPubkey, signers: &[(SolanaPubkey, bool)]) -> Vec<AccountMeta> {
+    let mut account_metas = vec![AccountMeta::new_readonly(multisig, false)];
+    account_metas.extend(signers.iter().map(|(signer, signed)| AccountMeta::new_readonly(*signer, *signed)));
+    account_metas
+}
+
+#[test]
+fn refund_with_multisig_threshold(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (multisig, signers) = multisig_authority(&mollusk, &mut swap);
+
+    // the maker doesn't sign, two of the three signers do
+    let remaining = authority_metas(multisig, &[(signers[0], true), (signers[1], false), (signers[2], true)]);
+    let (instruction, accounts) = swap.refund(&mollusk, false, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_a).unwrap()), AMOUNT);
+}
+
+#[test]
+fn refund_rejects_multisig_below_threshold(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (multisig, signers) = multisig_authority(&mollusk, &mut swap);
+
+    let remaining = authority_metas(multisig, &[(signers[0], true), (signers[1], false)]);
+    let (instruction, accounts) = swap.refund(&mollusk, false, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn refund_rejects_duplicate_multisig_signer(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let (multisig, signers) = multisig_authority(&mollusk, &mut swap);
+
+    // one signer passed twice only counts once
+    let remaining = authority_metas(multisig, &[(signers[0], true), (signers[0], true)]);
+    let (instruction, accounts) = swap.refund(&mollusk, false, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn refund_rejects_maker_alone_with_multisig_authority(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    multisig_authority(&mollusk, &mut swap);
+
+    let (instruction, accounts) = swap.refund(&mollusk, true, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn refund_rejects_another_multisig(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    multisig_authority(&mollusk, &mut swap);
+
+    // a multisig the signers control, but not the escrow's authority
+    let other = SolanaPubkey::new_unique();
+    let signers = [SolanaPubkey::new_unique()];
+    swap.set_account(other, create_multisig_account(&mollusk, 1, &signers));
+    swap.set_account(signers[0], AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+
+    let remaining = authority_metas(other, &[(signers[0], true)]);
+    let (instruction, accounts) = swap.refund(&mollusk, false, remaining);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
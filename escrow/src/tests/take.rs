@@ -0,0 +1,389 @@
+use mollusk_svm::Mollusk;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
+    instruction::{AccountMeta, Instruction},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::{
+    metadata::{metadata_address, METADATA_PROGRAM_ID},
+    processor::EscrowInstruction,
+    state::EscrowKind,
+    tests::{
+        metadata::metadata,
+        setup_mollusk,
+        create_escrow_account,
+        create_mint_account,
+        create_token_account,
+        fixed_escrow,
+        instruction_data,
+        token_amount,
+        Swap,
+        AMOUNT,
+        RECEIVE,
+    },
+};
+
+#[test]
+fn take_swaps_vault_for_receive(){
+    let mollusk = setup_mollusk();
+    let swap = Swap::new(&mollusk);
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), RECEIVE);
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_b).unwrap()), 0);
+    assert_eq!(result.get_account(&swap.escrow).unwrap().lamports(), 0);
+}
+
+#[test]
+fn take_rejects_account_without_escrow_discriminator(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    // same layout, but not written by make
+    swap.escrow_data.discriminator = *b"bid\0\0\0\0\0";
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn take_rejects_escrow_with_another_bump(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    // take re-derives the escrow from the stored bump
+    swap.escrow_data.bump = swap.escrow_data.bump.wrapping_sub(1);
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn take_rejects_maker_ta_b_of_someone_else(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    // a mint b account the taker controls instead of the maker's
+    let maker_ta_b = swap.maker_ta_b;
+    swap.set_account(maker_ta_b, create_token_account(swap.taker, &mollusk, swap.mint_b, 0));
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+// the (escrow, maker, maker_ta_b, vault) of one offer in take_many
+type TakeManyOffer = [SolanaPubkey; 4];
+
+fn swap_offer(swap: &Swap) -> TakeManyOffer {
+    [swap.escrow, swap.maker, swap.maker_ta_b, swap.vault]
+}
+
+// another maker's offer of AMOUNT mint a for RECEIVE mint b on the pair of `swap`,
+// the taker brings RECEIVE more of mint b for it
+fn add_offer(mollusk: &Mollusk, swap: &mut Swap) -> TakeManyOffer {
+    let maker = SolanaPubkey::new_unique();
+    let (escrow, escrow_data) = fixed_escrow(maker, swap.mint_a, swap.mint_b, RECEIVE);
+    let maker_ta_b = SolanaPubkey::new_unique();
+    let vault = SolanaPubkey::new_unique();
+
+    swap.set_account(maker, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+    swap.set_account(escrow, create_escrow_account(mollusk, &escrow_data, &[]));
+    swap.set_account(maker_ta_b, create_token_account(maker, mollusk, swap.mint_b, 0));
+    swap.set_account(vault, create_token_account(escrow, mollusk, swap.mint_a, AMOUNT));
+
+    let taker_ta_b = swap.taker_ta_b;
+    let taker_ta_b_amount = token_amount(&swap.account(mollusk, &taker_ta_b));
+    swap.set_account(taker_ta_b, create_token_account(swap.taker, mollusk, swap.mint_b, taker_ta_b_amount + RECEIVE));
+
+    [escrow, maker, maker_ta_b, vault]
+}
+
+// TakeMany of `offers` signed by the taker of `swap`
+fn take_many(
+    mollusk: &Mollusk,
+    swap: &Swap,
+    offers: &[TakeManyOffer],
+    max_receive: u64,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let mut account_metas = vec![
+        AccountMeta::new(swap.taker, true),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new_readonly(swap.mint_b, false),
+        AccountMeta::new(swap.taker_ta_a, false),
+        AccountMeta::new(swap.taker_ta_b, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+    for [escrow, maker, maker_ta_b, vault] in offers {
+        account_metas.extend([
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new(*maker_ta_b, false),
+            AccountMeta::new(*vault, false),
+        ]);
+    }
+    let data = instruction_data(EscrowInstruction::TakeMany { count: offers.len() as u8, max_receive });
+
+    swap.instruction(mollusk, data, account_metas)
+}
+
+#[test]
+fn take_many_fills_every_offer(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let offers = [swap_offer(&swap), add_offer(&mollusk, &mut swap), add_offer(&mollusk, &mut swap)];
+
+    let (instruction, accounts) = take_many(&mollusk, &swap, &offers, 3 * RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    // the taker gets every vault and pays every maker
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), 3 * AMOUNT);
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_b).unwrap()), 0);
+    for [escrow, _, maker_ta_b, vault] in offers {
+        assert_eq!(token_amount(result.get_account(&maker_ta_b).unwrap()), RECEIVE);
+        assert_eq!(result.get_account(&vault).unwrap().lamports(), 0);
+        assert_eq!(result.get_account(&escrow).unwrap().lamports(), 0);
+    }
+}
+
+#[test]
+fn take_many_rejects_total_above_max_receive(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let offers = [swap_offer(&swap), add_offer(&mollusk, &mut swap)];
+
+    let (instruction, accounts) = take_many(&mollusk, &swap, &offers, 2 * RECEIVE - 1);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn take_many_rejects_maker_ta_b_of_someone_else(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    let maker_ta_b = swap.maker_ta_b;
+    swap.set_account(maker_ta_b, create_token_account(swap.taker, &mollusk, swap.mint_b, 0));
+
+    let (instruction, accounts) = take_many(&mollusk, &swap, &[swap_offer(&swap)], RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn take_many_aborts_on_a_bad_offer(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let mut offers = vec![swap_offer(&swap), add_offer(&mollusk, &mut swap)];
+
+    // the last offer pays mint b to an account the taker controls instead of its maker's
+    let bad_offer = add_offer(&mollusk, &mut swap);
+    swap.set_account(bad_offer[2], create_token_account(swap.taker, &mollusk, swap.mint_b, 0));
+    offers.push(bad_offer);
+
+    let (instruction, accounts) = take_many(&mollusk, &swap, &offers, 3 * RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+
+    // the offers in front of the bad one are left as they were
+    for key in offers[..2].iter().flatten().chain([&swap.taker_ta_a, &swap.taker_ta_b]) {
+        let account = accounts.iter().find(|(account_key, _)| account_key == key).map(|(_, account)| account);
+        assert_eq!(result.get_account(key), account);
+    }
+}
+
+#[test]
+fn take_releases_basket_legs(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let leg = swap.add_basket_leg(&mollusk, 300);
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, leg.metas(leg.taker_ta));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&leg.taker_ta).unwrap()), 300);
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), RECEIVE);
+}
+
+#[test]
+fn take_rejects_missing_basket_leg(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    swap.add_basket_leg(&mollusk, 300);
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn take_of_multisig_escrow_needs_no_authority(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    // the authority only approves refund and release, taking is up to the taker
+    swap.escrow_data.authority = SolanaPubkey::new_unique();
+
+    let (instruction, accounts) = swap.take(&mollusk, RECEIVE, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), RECEIVE);
+}
+
+// taker_ta_b with an approval of `delegated_amount` for `delegate`
+fn create_delegated_token_account(
+    swap: &Swap,
+    mollusk: &Mollusk,
+    delegate: SolanaPubkey,
+    delegated_amount: u64,
+) -> AccountSharedData {
+    let mut token_account = create_token_account(swap.taker, mollusk, swap.mint_b, RECEIVE);
+    let mut token_account_data = spl_token::state::Account::unpack(token_account.data()).unwrap();
+    token_account_data.delegate = COption::Some(delegate);
+    token_account_data.delegated_amount = delegated_amount;
+    spl_token::state::Account::pack(token_account_data, token_account.data_as_mut_slice()).unwrap();
+    token_account
+}
+
+// a delegated take signed by `delegate`, the taker doesn't sign and approved `approved` on taker_ta_b
+fn setup_delegated_take(
+    mollusk: &Mollusk,
+    swap: &mut Swap,
+    delegate: SolanaPubkey,
+    approved: SolanaPubkey,
+    delegated_amount: u64,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let taker_ta_b = swap.taker_ta_b;
+    let approved_account = create_delegated_token_account(swap, mollusk, approved, delegated_amount);
+    swap.set_account(taker_ta_b, approved_account);
+    swap.set_account(delegate, AccountSharedData::new(1_000_000_000, 0, &solana_sdk::system_program::ID));
+
+    let mut account_metas = vec![AccountMeta::new_readonly(delegate, true)];
+    account_metas.extend(swap.take_metas(false));
+    let data = instruction_data(EscrowInstruction::TakeDelegated { max_receive: RECEIVE });
+
+    swap.instruction(mollusk, data, account_metas)
+}
+
+#[test]
+fn delegated_take_pays_with_approval(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let delegate = SolanaPubkey::new_unique();
+
+    let (instruction, accounts) = setup_delegated_take(&mollusk, &mut swap, delegate, delegate, RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    // mint a still goes to the taker
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), RECEIVE);
+}
+
+#[test]
+fn delegated_take_rejects_signer_without_approval(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+
+    // taker_ta_b is approved for someone else
+    let (instruction, accounts) = setup_delegated_take(&mollusk, &mut swap, SolanaPubkey::new_unique(), SolanaPubkey::new_unique(), RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn delegated_take_rejects_approval_below_price(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let delegate = SolanaPubkey::new_unique();
+
+    let (instruction, accounts) = setup_delegated_take(&mollusk, &mut swap, delegate, delegate, RECEIVE - 1);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn delegated_take_rejects_mint_a_to_the_delegate(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let delegate = SolanaPubkey::new_unique();
+
+    // the delegate routes mint a to its own account
+    let taker_ta_a = swap.taker_ta_a;
+    swap.set_account(taker_ta_a, create_token_account(delegate, &mollusk, swap.mint_a, 0));
+
+    let (instruction, accounts) = setup_delegated_take(&mollusk, &mut swap, delegate, delegate, RECEIVE);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+// a collection bid of AMOUNT mint a for one NFT of `collection`, the taker brings the NFT as mint b,
+// its metadata verifies `verified`
+fn collection_bid(mollusk: &Mollusk, collection: SolanaPubkey, verified: SolanaPubkey) -> (Swap, SolanaPubkey) {
+    let mut swap = Swap::new(mollusk);
+    swap.escrow_data.kind = EscrowKind::CollectionBid as u8;
+    swap.escrow_data.mint_b = SolanaPubkey::default();
+    swap.escrow_data.collection = collection;
+    swap.escrow_data.receive = 1;
+
+    let (mint_b, maker_ta_b, taker_ta_b) = (swap.mint_b, swap.maker_ta_b, swap.taker_ta_b);
+    swap.set_account(mint_b, create_mint_account(mollusk, 1, 0));
+    swap.set_account(taker_ta_b, create_token_account(swap.taker, mollusk, mint_b, 1));
+    swap.set_account(maker_ta_b, create_token_account(swap.maker, mollusk, mint_b, 0));
+
+    let nft_metadata = metadata_address(&mint_b);
+    let mut metadata_account = AccountSharedData::new(1_000_000_000, 0, &METADATA_PROGRAM_ID);
+    metadata_account.set_data_from_slice(&metadata(&mint_b, 0, Some((true, verified))));
+    swap.set_account(nft_metadata, metadata_account);
+
+    (swap, nft_metadata)
+}
+
+#[test]
+fn take_collection_bid_with_verified_nft(){
+    let mollusk = setup_mollusk();
+    let collection = SolanaPubkey::new_unique();
+    let (swap, metadata) = collection_bid(&mollusk, collection, collection);
+
+    let (instruction, accounts) = swap.take(&mollusk, 1, vec![AccountMeta::new_readonly(metadata, false)]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), AMOUNT);
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_b).unwrap()), 1);
+}
+
+#[test]
+fn take_collection_bid_rejects_nft_of_another_collection(){
+    let mollusk = setup_mollusk();
+    let (swap, metadata) = collection_bid(&mollusk, SolanaPubkey::new_unique(), SolanaPubkey::new_unique());
+
+    let (instruction, accounts) = swap.take(&mollusk, 1, vec![AccountMeta::new_readonly(metadata, false)]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn take_collection_bid_rejects_missing_metadata(){
+    let mollusk = setup_mollusk();
+    let collection = SolanaPubkey::new_unique();
+    let (swap, _) = collection_bid(&mollusk, collection, collection);
+
+    let (instruction, accounts) = swap.take(&mollusk, 1, vec![]);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
@@ -0,0 +1,232 @@
+use mollusk_svm::Mollusk;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey as SolanaPubkey,
+};
+
+use crate::{
+    processor::{EscrowInstruction, VestingArgs},
+    state::{Escrow, EscrowKind, Vesting},
+    tests::{
+        setup_mollusk,
+        create_token_account,
+        instruction_data,
+        token_amount,
+        Swap,
+    },
+};
+
+const VESTING: Vesting = Vesting {
+    beneficiary: Pubkey::new_from_array([1; 32]),
+    total: 1_200,
+    claimed: 0,
+    start_ts: 0,
+    cliff_ts: 300,
+    end_ts: 1_200,
+};
+
+#[test]
+fn nothing_unlocked_before_cliff(){
+    assert_eq!(VESTING.unlocked(i64::MIN), 0);
+    assert_eq!(VESTING.unlocked(0), 0);
+    assert_eq!(VESTING.unlocked(299), 0);
+}
+
+#[test]
+fn cliff_unlocks_time_since_start(){
+    assert_eq!(VESTING.unlocked(300), 300);
+}
+
+#[test]
+fn unlocks_linearly_after_cliff(){
+    assert_eq!(VESTING.unlocked(301), 301);
+    assert_eq!(VESTING.unlocked(600), 600);
+    assert_eq!(VESTING.unlocked(1_199), 1_199);
+}
+
+#[test]
+fn everything_unlocked_at_end(){
+    assert_eq!(VESTING.unlocked(1_200), 1_200);
+    assert_eq!(VESTING.unlocked(i64::MAX), 1_200);
+}
+
+#[test]
+fn claimable_subtracts_claimed(){
+    let vesting = Vesting { claimed: 500, ..VESTING };
+    assert_eq!(vesting.claimable(299), 0);
+    assert_eq!(vesting.claimable(400), 0);
+    assert_eq!(vesting.claimable(600), 100);
+    assert_eq!(vesting.claimable(1_200), 700);
+}
+
+#[test]
+fn invalid_schedules(){
+    assert!(VESTING.is_valid());
+    assert!(Vesting { cliff_ts: 0, ..VESTING }.is_valid());
+    assert!(Vesting { cliff_ts: 1_200, ..VESTING }.is_valid());
+    assert!(!Vesting { cliff_ts: -1, ..VESTING }.is_valid());
+    assert!(!Vesting { cliff_ts: 1_201, ..VESTING }.is_valid());
+    assert!(!Vesting { start_ts: 1_200, cliff_ts: 1_200, ..VESTING }.is_valid());
+}
+
+// the accounts of `swap` before make_vesting, the maker holds VESTING.total of mint a and the escrow doesn't exist
+fn setup_make_vesting(mollusk: &Mollusk, swap: &mut Swap, args: VestingArgs) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    swap.set_account(swap.escrow, AccountSharedData::default());
+    swap.set_account(swap.maker_ta_a, create_token_account(swap.maker, mollusk, swap.mint_a, VESTING.total));
+    swap.set_account(swap.vault, create_token_account(swap.escrow, mollusk, swap.mint_a, 0));
+
+    let account_metas = vec![
+        AccountMeta::new(swap.maker, true),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new(swap.maker_ta_a, false),
+        AccountMeta::new(swap.vault, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::MakeVesting(args)), account_metas)
+}
+
+// VESTING of the whole maker balance for the taker of `swap`
+fn vesting_args(swap: &Swap) -> VestingArgs {
+    VestingArgs {
+        amount: VESTING.total,
+        beneficiary: swap.taker,
+        start_ts: VESTING.start_ts,
+        cliff_ts: VESTING.cliff_ts,
+        end_ts: VESTING.end_ts,
+        escrow_bump: swap.escrow_data.bump,
+    }
+}
+
+// a vesting escrow as make_vesting leaves it with `claimed` already withdrawn, the taker of `swap` is the beneficiary
+fn vesting_swap(mollusk: &Mollusk, claimed: u64) -> Swap {
+    let mut swap = Swap::new(mollusk);
+    swap.escrow_data.kind = EscrowKind::Vesting as u8;
+    swap.escrow_data.mint_b = SolanaPubkey::default();
+    swap.escrow_data.receive = 0;
+    swap.escrow_data.vesting = Vesting { beneficiary: swap.taker, claimed, ..VESTING };
+
+    let vault = swap.vault;
+    swap.set_account(vault, create_token_account(swap.escrow, mollusk, swap.mint_a, VESTING.total - claimed));
+
+    swap
+}
+
+// claim signed by the beneficiary, the taker of `swap`
+fn claim(mollusk: &Mollusk, swap: &Swap) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let account_metas = vec![
+        AccountMeta::new(swap.taker, true),
+        AccountMeta::new(swap.maker, false),
+        AccountMeta::new_readonly(swap.mint_a, false),
+        AccountMeta::new(swap.escrow, false),
+        AccountMeta::new(swap.vault, false),
+        AccountMeta::new(swap.taker_ta_a, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+    ];
+
+    swap.instruction(mollusk, instruction_data(EscrowInstruction::Claim), account_metas)
+}
+
+#[test]
+fn make_vesting_locks_amount(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let args = vesting_args(&swap);
+
+    let (instruction, accounts) = setup_make_vesting(&mollusk, &mut swap, args);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    let escrow_data = Escrow::load(escrow_account.data()).unwrap();
+    assert_eq!(escrow_data.kind, EscrowKind::Vesting as u8);
+    assert_eq!(escrow_data.vesting.beneficiary, swap.taker);
+    assert_eq!(escrow_data.vesting.total, VESTING.total);
+    assert_eq!(escrow_data.vesting.claimed, 0);
+    assert_eq!(escrow_data.vesting.cliff_ts, VESTING.cliff_ts);
+
+    assert_eq!(token_amount(result.get_account(&swap.vault).unwrap()), VESTING.total);
+    assert_eq!(token_amount(result.get_account(&swap.maker_ta_a).unwrap()), 0);
+}
+
+#[test]
+fn make_vesting_rejects_invalid_schedule(){
+    let mollusk = setup_mollusk();
+    let mut swap = Swap::new(&mollusk);
+    let mut args = vesting_args(&swap);
+    args.cliff_ts = VESTING.end_ts + 1;
+
+    let (instruction, accounts) = setup_make_vesting(&mollusk, &mut swap, args);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn claim_withdraws_unlocked_part(){
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 600;
+    let swap = vesting_swap(&mollusk, 0);
+
+    let (instruction, accounts) = claim(&mollusk, &swap);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), 600);
+    assert_eq!(token_amount(result.get_account(&swap.vault).unwrap()), VESTING.total - 600);
+
+    // the escrow stays open for the rest
+    let escrow_account = result.get_account(&swap.escrow).expect("Failed to get escrow account");
+    assert_eq!(Escrow::load(escrow_account.data()).unwrap().vesting.claimed, 600);
+}
+
+#[test]
+fn claim_rejects_before_cliff(){
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = VESTING.cliff_ts - 1;
+    let swap = vesting_swap(&mollusk, 0);
+
+    let (instruction, accounts) = claim(&mollusk, &swap);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn claim_rejects_someone_else(){
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = 600;
+    let mut swap = vesting_swap(&mollusk, 0);
+    swap.escrow_data.vesting.beneficiary = SolanaPubkey::new_unique();
+
+    let (instruction, accounts) = claim(&mollusk, &swap);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(result.program_result.is_err());
+}
+
+#[test]
+fn final_claim_closes_vault_and_escrow(){
+    let mut mollusk = setup_mollusk();
+    mollusk.sysvars.clock.unix_timestamp = VESTING.end_ts;
+    let swap = vesting_swap(&mollusk, 600);
+
+    let (instruction, accounts) = claim(&mollusk, &swap);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(!result.program_result.is_err());
+
+    assert_eq!(token_amount(result.get_account(&swap.taker_ta_a).unwrap()), VESTING.total - 600);
+
+    // both rents go back to the maker
+    assert_eq!(result.get_account(&swap.vault).unwrap().lamports(), 0);
+    assert_eq!(result.get_account(&swap.escrow).unwrap().lamports(), 0);
+    assert_eq!(
+        result.get_account(&swap.maker).unwrap().lamports(),
+        1_000_000_000
+            + mollusk.sysvars.rent.minimum_balance(spl_token::state::Account::LEN)
+            + mollusk.sysvars.rent.minimum_balance(Escrow::space(0))
+    );
+}
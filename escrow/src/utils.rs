@@ -0,0 +1,324 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use std::cmp::Ordering;
+use spl_token::{instruction::{close_account, transfer_checked}, state::{Account, Mint}};
+
+use crate::{
+    metadata::{metadata_address, verified_collection, METADATA_PROGRAM_ID},
+    state::{Escrow, Multisig, OrderBook, OrderBookEntry},
+};
+
+// basket legs are passed as remaining accounts in groups of (mint, user token account, vault)
+pub const BASKET_ACCOUNTS: usize = 3;
+
+// a multisig authority is passed as (multisig, signers...) after any basket accounts
+pub fn check_multisig(program_id: &Pubkey, multisig: &AccountInfo, signers: &[AccountInfo]) -> ProgramResult {
+    assert_eq!(multisig.owner, program_id);
+    let multisig_data = *Multisig::load(&multisig.try_borrow_data()?)?;
+
+    let signed = signers.iter().filter(|signer| signer.is_signer).map(|signer| signer.key);
+    assert!(multisig_data.is_approved_by(signed));
+
+    Ok(())
+}
+
+// the maker approves for itself unless the escrow was made with a multisig authority
+pub fn check_authority(
+    program_id: &Pubkey,
+    escrow_data: &Escrow,
+    maker: &AccountInfo,
+    authority_accounts: &[AccountInfo],
+) -> ProgramResult {
+    if escrow_data.authority == escrow_data.maker {
+        // check that maker is signer
+        assert!(maker.is_signer);
+        return Ok(());
+    }
+
+    let [multisig, signers @ ..] = authority_accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    assert_eq!(multisig.key, &escrow_data.authority);
+
+    check_multisig(program_id, multisig, signers)
+}
+
+// an NFT is a mint without decimals that only ever has one token
+pub fn check_nft_mint(mint: &AccountInfo) -> ProgramResult {
+    let mint_data = Mint::unpack(&mint.try_borrow_data()?)?;
+    assert!(mint_data.decimals == 0 && mint_data.supply == 1);
+    Ok(())
+}
+
+// `metadata` has to be the metadata account of `mint` with `collection` verified
+pub fn check_collection(mint: &AccountInfo, metadata: &AccountInfo, collection: &Pubkey) -> ProgramResult {
+    assert_eq!(metadata.owner, &METADATA_PROGRAM_ID);
+    assert_eq!(metadata.key, &metadata_address(mint.key));
+    assert_eq!(verified_collection(&metadata.try_borrow_data()?, mint.key)?, Some(*collection));
+    Ok(())
+}
+
+pub fn check_token_account(token_account: &AccountInfo, mint: &Pubkey, owner: &Pubkey) -> ProgramResult {
+    let token_account_data = Account::unpack(&token_account.try_borrow_data()?)?;
+    assert_eq!(&token_account_data.mint, mint);
+    assert_eq!(&token_account_data.owner, owner);
+    Ok(())
+}
+
+// a vault is a token account for `mint` whose authority is the escrow (or bid) PDA
+pub fn check_vault(vault: &AccountInfo, mint: &Pubkey, escrow: &Pubkey) -> ProgramResult {
+    check_token_account(vault, mint, escrow)
+}
+
+// moves `amount` from a token account the signer `authority` owns into a vault
+pub fn deposit<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+) -> ProgramResult {
+    let decimals = Mint::unpack(&mint.try_borrow_data()?)?.decimals;
+
+    invoke(
+        &transfer_checked(
+           token_program.key,
+           from.key,
+           mint.key,
+           vault.key,
+           authority.key,
+           &[],
+           amount,
+           decimals,
+        )?,
+        &[
+            authority.clone(),
+            from.clone(),
+            mint.clone(),
+            vault.clone(),
+            token_program.clone(),
+        ]
+    )
+}
+
+// closes a program owned account and sends its rent to `destination`
+pub fn close_program_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    account.try_borrow_mut_data()?.fill(0);
+    let destination_orig_lamports = destination.lamports();
+    **destination.lamports.borrow_mut() = destination_orig_lamports.checked_add(account.lamports()).ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.lamports.borrow_mut() = 0;
+    Ok(())
+}
+
+// sends `amount` out of the vault to `to`
+// `escrow` is the vault authority, `escrow_seeds` its signer seeds
+pub fn withdraw_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    escrow_seeds: &[&[u8]],
+) -> ProgramResult {
+    let decimals = Mint::unpack(&mint.try_borrow_data()?)?.decimals;
+
+    invoke_signed(
+        &transfer_checked(
+            token_program.key,
+            vault.key,
+            mint.key,
+            to.key,
+            escrow.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            vault.clone(),
+            mint.clone(),
+            to.clone(),
+            escrow.clone(),
+            token_program.clone(),
+        ],
+        &[escrow_seeds]
+    )
+}
+
+// closes an empty vault and sends its rent to `close_to`
+pub fn close_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    close_to: &AccountInfo<'info>,
+    escrow_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &close_account(
+            token_program.key,
+            vault.key,
+            close_to.key,
+            escrow.key,
+            &[],
+        )?,
+        &[
+            vault.clone(),
+            close_to.clone(),
+            escrow.clone(),
+            token_program.clone(),
+        ],
+        &[escrow_seeds]
+    )
+}
+
+// sends the whole vault balance to `to` and closes the vault to `close_to`, returns the amount sent
+pub fn release_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    close_to: &AccountInfo<'info>,
+    escrow_seeds: &[&[u8]],
+) -> Result<u64, ProgramError> {
+    let amount = Account::unpack(&vault.try_borrow_data()?)?.amount;
+
+    withdraw_vault(token_program, escrow, vault, mint, to, amount, escrow_seeds)?;
+    close_vault(token_program, escrow, vault, close_to, escrow_seeds)?;
+
+    Ok(amount)
+}
+
+// adds an offer to the OrderBook of its pair, creating the index on first use,
+// `payer` tops up the rent for the added entry
+pub fn order_book_insert<'info>(
+    program_id: &Pubkey,
+    index: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    entry: OrderBookEntry,
+) -> ProgramResult {
+    assert!(index.is_writable);
+    let (expected_index, index_bump) = Pubkey::find_program_address(&[b"index", mint_a.as_ref(), mint_b.as_ref()], program_id);
+    assert_eq!(&expected_index, index.key);
+
+    if index.data_is_empty() {
+        let index_seeds: &[&[u8]] = &[b"index", mint_a.as_ref(), mint_b.as_ref(), &[index_bump]];
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                index.key,
+                Rent::get()?.minimum_balance(OrderBook::space(0)),
+                OrderBook::space(0) as u64,
+                program_id,
+            ),
+            &[payer.clone(), index.clone(), system_program.clone()],
+            &[index_seeds],
+        )?;
+
+        let mut index_account_data = index.try_borrow_mut_data()?;
+        let order_book = bytemuck::try_from_bytes_mut::<OrderBook>(&mut index_account_data)
+            .map_err(|_| ProgramError::AccountBorrowFailed)?;
+
+        *order_book = OrderBook {
+            discriminator: OrderBook::DISCRIMINATOR,
+            version: OrderBook::VERSION,
+            bump: index_bump,
+            _padding: [0; 2],
+            len: 0,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+        };
+    }
+
+    assert_eq!(index.owner, program_id);
+    let len = OrderBook::load(&index.try_borrow_data()?)?.len as usize;
+
+    // a full index keeps the better offers, the new one replaces the worst or isn't listed at all
+    if len == OrderBook::MAX_ENTRIES {
+        let mut index_account_data = index.try_borrow_mut_data()?;
+        let (position, worst) = OrderBook::worst_offer(&index_account_data)?
+            .map(|(position, worst)| (position, *worst))
+            .ok_or(ProgramError::InvalidAccountData)?;
+        assert_eq!(entry.cmp_price(&worst), Ordering::Less);
+
+        let entries = &mut index_account_data[OrderBook::LEN..];
+        entries[position * OrderBook::ENTRY_LEN..(position + 1) * OrderBook::ENTRY_LEN].copy_from_slice(bytemuck::bytes_of(&entry));
+        return Ok(());
+    }
+
+    let space = OrderBook::space(len + 1);
+    let rent = Rent::get()?.minimum_balance(space).saturating_sub(index.lamports());
+    if rent > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, index.key, rent),
+            &[payer.clone(), index.clone(), system_program.clone()],
+        )?;
+    }
+    index.realloc(space, false)?;
+
+    let mut index_account_data = index.try_borrow_mut_data()?;
+    let (header, entries) = index_account_data.split_at_mut(OrderBook::LEN);
+    bytemuck::try_from_bytes_mut::<OrderBook>(header)
+        .map_err(|_| ProgramError::InvalidAccountData)?
+        .len += 1;
+    entries[len * OrderBook::ENTRY_LEN..].copy_from_slice(bytemuck::bytes_of(&entry));
+
+    Ok(())
+}
+
+// removes a taken or refunded offer from its OrderBook, the freed rent goes to `close_to`,
+// an offer a better one evicted from a full index isn't listed anymore and leaves it as is
+pub fn order_book_remove(
+    program_id: &Pubkey,
+    index: &AccountInfo,
+    escrow_data: &Escrow,
+    escrow: &Pubkey,
+    close_to: &AccountInfo,
+) -> ProgramResult {
+    assert!(index.is_writable);
+    assert_eq!(index.owner, program_id);
+    let order_book = *OrderBook::load(&index.try_borrow_data()?)?;
+    let index_seeds: &[&[u8]] = &[b"index", escrow_data.mint_a.as_ref(), escrow_data.mint_b.as_ref(), &[order_book.bump]];
+    let expected_index = Pubkey::create_program_address(index_seeds, program_id)?;
+    assert_eq!(&expected_index, index.key);
+
+    let len = order_book.len as usize;
+    {
+        let mut index_account_data = index.try_borrow_mut_data()?;
+        let Some(position) = OrderBook::entries(&index_account_data)?
+            .iter()
+            .position(|entry| &entry.escrow == escrow) else {
+            return Ok(());
+        };
+
+        // move the last entry into the freed slot
+        let (header, entries) = index_account_data.split_at_mut(OrderBook::LEN);
+        entries.copy_within((len - 1) * OrderBook::ENTRY_LEN.., position * OrderBook::ENTRY_LEN);
+        bytemuck::try_from_bytes_mut::<OrderBook>(header)
+            .map_err(|_| ProgramError::InvalidAccountData)?
+            .len -= 1;
+    }
+
+    let space = OrderBook::space(len - 1);
+    index.realloc(space, false)?;
+
+    let surplus = index.lamports().saturating_sub(Rent::get()?.minimum_balance(space));
+    **index.lamports.borrow_mut() -= surplus;
+    **close_to.lamports.borrow_mut() = close_to.lamports().checked_add(surplus).ok_or(ProgramError::ArithmeticOverflow)?;
+
+    Ok(())
+}
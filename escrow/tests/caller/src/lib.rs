@@ -0,0 +1,90 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint,
+    entrypoint::ProgramResult,
+    declare_id,
+    pubkey::Pubkey,
+    program_error::ProgramError,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use escrow::cpi;
+
+// test program for escrow's cpi module, its PDA [b"maker"] makes and refunds offers
+// escrow's build.rs builds it for the tests with `cargo build-sbf`
+declare_id!("33333333333333333333333333333333333333333333");
+
+entrypoint!(process_instruction);
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum CallerInstruction {
+    Make { amount: u64, receive: u64, escrow_bump: u8 },
+    Refund,
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+
+    let [
+        escrow_program,
+        maker,
+        mint_a,
+        mint_b, // only used by make
+        escrow,
+        maker_ta_a,
+        vault,
+        token_program,
+        system_program
+     ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (expected_maker, maker_bump) = Pubkey::find_program_address(&[b"maker"], program_id);
+    assert_eq!(&expected_maker, maker.key);
+    let maker_seeds: &[&[u8]] = &[b"maker", &[maker_bump]];
+
+    match CallerInstruction::try_from_slice(data)? {
+        CallerInstruction::Make { amount, receive, escrow_bump } => cpi::make(
+            escrow_program,
+            cpi::Make {
+                maker,
+                mint_a,
+                mint_b,
+                escrow,
+                maker_ta_a,
+                vault,
+                token_program,
+                system_program,
+                remaining: &[],
+            },
+            cpi::EscrowArgs {
+                maker: *maker.key,
+                amount,
+                receive,
+                escrow_bump,
+                basket_amounts: vec![],
+                terms: cpi::EscrowTerms::Fixed,
+                authority: None,
+                nft: None,
+                index: false,
+            },
+            &[maker_seeds],
+        ),
+        CallerInstruction::Refund => cpi::refund(
+            escrow_program,
+            cpi::Refund {
+                maker,
+                mint_a,
+                escrow,
+                maker_ta_a,
+                vault,
+                token_program,
+                system_program,
+                remaining: &[],
+            },
+            &[maker_seeds],
+        ),
+    }
+}
@@ -1,4 +1,4 @@
+// defaults for campaigns initialized without their own contribution limits
 pub const MIN_AMOUNT_TO_RAISE: u64 = 3;
-pub const SECONDS_TO_DAYS: i64 = 86400;
-pub const MAX_CONTRIBUTION_PERCENTAGE: u64 = 10;
-pub const PERCENTAGE_SCALER: u64 = 100;
+pub const MAX_CONTRIBUTION_BPS: u64 = 1_000; // 10% of the goal per contributor
+pub const BPS_SCALER: u64 = 10_000;
@@ -0,0 +1,26 @@
+use pinocchio::program_error::ProgramError;
+
+// business rule violations, returned as ProgramError::Custom(error as u32)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FundraiserError {
+    BelowMinimum, // contribution below the campaign's min_contribution
+    GoalExceeded, // contribution would raise more than amount_to_raise
+    ContributionCapExceeded, // contributor would give more than the campaign's per-wallet cap
+    DeadlineReached, // contribution from the deadline on
+    DeadlineNotReached, // refund before the deadline
+    GoalNotReached, // checker before the goal is met
+    GoalReached, // refund after the goal is met
+    InvalidPda, // account is not the expected PDA
+    InvalidContributionLimits, // initialize with limits no contribution could meet
+    InvalidMint, // vault that doesn't hold mint_to_raise
+    InvalidStatus, // instruction not allowed in the fundraiser's current status
+    InvalidDuration, // initialize with a campaign that is over before it starts
+    InvalidGoal, // initialize with nothing to raise
+    InvalidContributorAmount, // refund of more than the fundraiser has raised
+}
+
+impl From<FundraiserError> for ProgramError {
+    fn from(error: FundraiserError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
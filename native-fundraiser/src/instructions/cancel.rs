@@ -0,0 +1,47 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    ProgramResult,
+    sysvars::{clock::Clock, Sysvar},
+};
+use crate::error::FundraiserError;
+use crate::state::{Fundraiser, FundraiserStatus};
+use crate::utils::validate_pda;
+
+pub fn cancel(accounts: &[AccountInfo]) -> ProgramResult {
+
+    let [
+        maker,
+        fundraiser,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if fundraiser.owner() != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let mut fundraiser_data: Fundraiser = *bytemuck::try_from_bytes::<Fundraiser>(&fundraiser.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // the maker seed makes sure only the maker of this campaign cancels it
+    validate_pda(&[
+        b"fundraiser",
+        maker.key().as_ref(),
+        &fundraiser_data.campaign_id.to_le_bytes(),
+        &[fundraiser_data.bump as u8]
+    ], &crate::ID, fundraiser.key())?;
+
+    // a successful campaign is withdrawn, not cancelled, and a failed one already refunds
+    if fundraiser_data.status_at(Clock::get()?.unix_timestamp)? != FundraiserStatus::Active {
+        return Err(FundraiserError::InvalidStatus.into());
+    }
+
+    // the funds stay in the vault, from now on contributors refund them without waiting for the deadline
+    fundraiser_data.status = FundraiserStatus::Cancelled as u8;
+    fundraiser.try_borrow_mut_data()?.copy_from_slice(bytemuck::bytes_of(&fundraiser_data));
+
+    Ok(())
+}
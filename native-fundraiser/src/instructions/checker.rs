@@ -3,15 +3,15 @@ use pinocchio::{
     program_error::ProgramError,
     ProgramResult,
     instruction::{Seed, Signer},
+    sysvars::{clock::Clock, Sysvar},
 };
-use pinocchio_token::instructions::Transfer;
-use crate::processor::CheckerArgs;
-use crate::state::Fundraiser;
-use crate::utils::validate_pda;
+use pinocchio_token::{instructions::{CloseAccount, Transfer}, state::TokenAccount};
+use crate::error::FundraiserError;
+use crate::state::{Fundraiser, FundraiserStatus};
+use crate::utils::{close_program_account, validate_pda, validate_vault_mint};
 
 pub fn checker(
     accounts: &[AccountInfo],
-    args: &[u8]
 ) -> ProgramResult {
 
     let [ 
@@ -24,51 +24,68 @@ pub fn checker(
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    assert!(maker.is_signer());
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-    let CheckerArgs {
-        fundraiser_bump,
-        vault_bump,
-    } = CheckerArgs::try_from(args)?;
+    // a closed fundraiser has no data left, so a repeated checker call stops here
+    if fundraiser.owner() != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let fundraiser_data: Fundraiser = *bytemuck::try_from_bytes::<Fundraiser>(&fundraiser.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
 
-    let u8_fundraiser_bump = fundraiser_bump as u8;
-    let fundraiser_bump_bytes = u8_fundraiser_bump.to_le_bytes();
+    // the maker seed makes sure only the maker of this campaign withdraws it
     validate_pda(&[
         b"fundraiser",
         maker.key().as_ref(),
-        &fundraiser_bump_bytes
-    ], &crate::ID, fundraiser.key());
+        &fundraiser_data.campaign_id.to_le_bytes(),
+        &[fundraiser_data.bump as u8]
+    ], &crate::ID, fundraiser.key())?;
 
-    let u8_vault_bump = vault_bump as u8;
+    // check if the fundraising goal has been reached, a cancelled or failed fundraiser belongs to its contributors
+    match fundraiser_data.status_at(Clock::get()?.unix_timestamp)? {
+        FundraiserStatus::GoalReached => {}
+        FundraiserStatus::Active => return Err(FundraiserError::GoalNotReached.into()),
+        _ => return Err(FundraiserError::InvalidStatus.into()),
+    }
+
+    let u8_vault_bump = fundraiser_data.vault_bump as u8;
     let vault_bump_bytes = u8_vault_bump.to_le_bytes();
     validate_pda(&[
         b"vault",
         fundraiser.key().as_ref(),
         &vault_bump_bytes
-    ], &crate::ID, vault.key());
-
-    let fundraiser_data: Fundraiser = *bytemuck::try_from_bytes::<Fundraiser>(&fundraiser.try_borrow_mut_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+    ], &crate::ID, vault.key())?;
+    validate_vault_mint(vault, &fundraiser_data.mint_to_raise)?;
 
-    // check if the fundraising goal has been reached
-    assert!(fundraiser_data.current_amount >= fundraiser_data.amount_to_raise);
-    
-    
     let signer_seeds = [
         Seed::from(b"vault"), 
         Seed::from(fundraiser.key().as_ref()), 
         Seed::from(vault_bump_bytes.as_ref())
     ];
-    let signer = Signer::from(&signer_seeds);
+    let signer = [Signer::from(&signer_seeds)];
 
-    // transfer the funds to the maker
+    // transfer the whole balance to the maker, tokens sent to the vault outside of contribute
+    // are on top of current_amount and CloseAccount refuses a vault that isn't empty
+    let vault_amount = TokenAccount::from_account_info(vault)?.amount();
     Transfer {
         from: vault,
         to: maker_ata,
         authority: vault,
-        amount: fundraiser_data.current_amount,
-    }.invoke_signed(&[signer])?;
+        amount: vault_amount,
+    }.invoke_signed(&signer)?;
+
+    // close the empty vault and the fundraiser, the rent goes back to the maker
+    CloseAccount {
+        account: vault,
+        destination: maker,
+        authority: vault,
+    }.invoke_signed(&signer)?;
+
+    // a withdrawn campaign is over, closing the fundraiser is terminal rather than a status
+    close_program_account(fundraiser, maker)?;
 
-    // TODO:close fundraiser and contributor accounts?
+    // contributor accounts stay open, their rent belongs to the contributors
 
     Ok(())
 }
\ No newline at end of file
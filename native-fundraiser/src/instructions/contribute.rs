@@ -1,14 +1,18 @@
 use pinocchio::{
     account_info::AccountInfo,
+    instruction::{Seed, Signer},
     program_error::ProgramError, 
-    sysvars::{clock::Clock, Sysvar}, 
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, rent::Rent, Sysvar}, 
     ProgramResult,
 };
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer as SystemTransfer};
 use pinocchio_token::instructions::Transfer;
-use crate::{constants::PERCENTAGE_SCALER, processor::ContributeArgs};
-use crate::constants::{MIN_AMOUNT_TO_RAISE, MAX_CONTRIBUTION_PERCENTAGE, SECONDS_TO_DAYS};
-use crate::state::{Fundraiser, Contributor};
-use crate::utils::validate_pda;
+use crate::constants::BPS_SCALER;
+use crate::processor::ContributeArgs;
+use crate::error::FundraiserError;
+use crate::state::{Fundraiser, FundraiserStatus, Contributor};
+use crate::utils::{validate_pda, validate_vault_mint};
 pub fn contribute(
     accounts: &[AccountInfo],
     args: &[u8]
@@ -17,50 +21,153 @@ pub fn contribute(
         contributor,
         fundraiser,
         contributor_ta,
-        contributor_account, // account that saves the contribution amount of the contributor
+        contributor_account, // PDA that saves the contribution amount of the contributor (seeds: [b"contributor", fundraiser, contributor] + bump), created on the first contribution
         vault, // account that receives the contribution
         _token_program,
+        _system_program,
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    assert!(contributor.is_signer());
+    if !contributor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
     let ContributeArgs {
         amount,
-        vault_bump,
+        contributor_bump,
     } = ContributeArgs::try_from(args)?;
 
-    let u8_vault_bump = vault_bump as u8;
+    // Borrow the data and immediately convert it
+    if fundraiser.owner() != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let mut fundraiser_data: Fundraiser = *bytemuck::try_from_bytes::<Fundraiser>(&fundraiser.try_borrow_mut_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // check if fundraiser is correct, the vault and contributor seeds hang off its address
+    validate_pda(&[
+        b"fundraiser",
+        fundraiser_data.maker.as_ref(),
+        &fundraiser_data.campaign_id.to_le_bytes(),
+        &[fundraiser_data.bump as u8]
+    ], &crate::ID, fundraiser.key())?;
 
     // check if vault is correct
     validate_pda(&[
         b"vault",
         fundraiser.key().as_ref(),
-        &[u8_vault_bump]
-    ], &crate::ID, vault.key());
+        &[fundraiser_data.vault_bump as u8]
+    ], &crate::ID, vault.key())?;
+    validate_vault_mint(vault, &fundraiser_data.mint_to_raise)?;
 
-    // Check if the amount to contribute meets the minimum amount required
-    assert!(amount >= MIN_AMOUNT_TO_RAISE);
+    // only an active fundraiser takes contributions, a cancelled one only pays back
+    if fundraiser_data.status()? != FundraiserStatus::Active {
+        return Err(FundraiserError::InvalidStatus.into());
+    }
 
-    // TODO: check if we need to validate the fundraiser PDA
-    // Borrow the data and immediately convert it
-    let mut fundraiser_data: Fundraiser = *bytemuck::try_from_bytes::<Fundraiser>(&fundraiser.try_borrow_mut_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+    // Check if the amount to contribute meets the minimum amount of the campaign
+    if amount < fundraiser_data.min_contribution {
+        return Err(FundraiserError::BelowMinimum.into());
+    }
 
     // Check if the total amount raised is less than the maximum amount to raise
-    assert!(amount + fundraiser_data.current_amount <= fundraiser_data.amount_to_raise);
+    let current_amount = fundraiser_data.current_amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    if current_amount > fundraiser_data.amount_to_raise {
+        return Err(FundraiserError::GoalExceeded.into());
+    }
+
+    // check if contributor account is correct, the fundraiser is part of the seeds so one contributor can back several campaigns
+    let u8_contributor_bump = contributor_bump as u8;
+    let contributor_bump_bytes = u8_contributor_bump.to_le_bytes();
+    validate_pda(&[
+        b"contributor",
+        fundraiser.key().as_ref(),
+        contributor.key().as_ref(),
+        &contributor_bump_bytes
+    ], &crate::ID, contributor_account.key())?;
+
+    if contributor_account.data_is_empty() {
+        // first contribution, only the canonical bump may create the account or a contributor could hold one per bump
+        let (_, canonical_bump) = find_program_address(&[
+            b"contributor",
+            fundraiser.key().as_ref(),
+            contributor.key().as_ref()
+        ], &crate::ID);
+        if u8_contributor_bump != canonical_bump {
+            return Err(FundraiserError::InvalidPda.into());
+        }
+
+        // the contributor pays for its own account
+        let signer_seeds = [
+            Seed::from(b"contributor"),
+            Seed::from(fundraiser.key().as_ref()),
+            Seed::from(contributor.key().as_ref()),
+            Seed::from(contributor_bump_bytes.as_ref())
+        ];
+        let signer = [Signer::from(&signer_seeds)];
+
+        let minimum_balance = Rent::get()?.minimum_balance(Contributor::LEN);
+        if contributor_account.lamports() == 0 {
+            CreateAccount {
+                from: contributor,
+                to: contributor_account,
+                lamports: minimum_balance,
+                space: Contributor::LEN as u64,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&signer)?;
+        } else {
+            // anyone can send lamports to the address beforehand and CreateAccount refuses a funded account,
+            // top it up to rent exemption and take it over instead
+            let missing = minimum_balance.saturating_sub(contributor_account.lamports());
+            if missing > 0 {
+                SystemTransfer {
+                    from: contributor,
+                    to: contributor_account,
+                    lamports: missing,
+                }
+                .invoke()?;
+            }
+
+            Allocate {
+                account: contributor_account,
+                space: Contributor::LEN as u64,
+            }
+            .invoke_signed(&signer)?;
+
+            Assign {
+                account: contributor_account,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        contributor_account.try_borrow_mut_data()?.copy_from_slice(bytemuck::bytes_of(&Contributor {
+            amount: 0,
+            contributor_bump,
+        }));
+    } else if contributor_account.owner() != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
 
-    // TODO: check if the contributer account exists and create it if it doesn't or can we expect it to be created by the frontend?
     let mut contributor_data: Contributor = *bytemuck::try_from_bytes::<Contributor>(&contributor_account.try_borrow_mut_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
 
-    // Check if the amount to contribute is less than the maximum allowed contribution per contributor
-    let amount_allowed = (fundraiser_data.amount_to_raise * MAX_CONTRIBUTION_PERCENTAGE) / PERCENTAGE_SCALER;
-    assert!(amount + contributor_data.amount <= amount_allowed);
-    
-    // check if the fundraising duration has been reached
+    // Check if the amount to contribute is less than the maximum allowed contribution per contributor,
+    // the share of the goal and the absolute cap if the campaign has one
+    let mut amount_allowed = (fundraiser_data.amount_to_raise as u128 * fundraiser_data.max_contribution_bps as u128 / BPS_SCALER as u128) as u64;
+    if fundraiser_data.max_contribution != 0 {
+        amount_allowed = amount_allowed.min(fundraiser_data.max_contribution);
+    }
+    let contributed = contributor_data.amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    if contributed > amount_allowed {
+        return Err(FundraiserError::ContributionCapExceeded.into());
+    }
+
+    // check that the fundraising duration has not been reached, from the deadline on only refunds are possible
     let current_time = Clock::get()?.unix_timestamp; 
-    let _fundraiser_end_time = (current_time - fundraiser_data.time_started) / SECONDS_TO_DAYS;
-    // assert!(fundraiser_end_time >= fundraiser_data.duration); // TODO: fails in test because current_time is 0 - fix test
+    if current_time - fundraiser_data.time_started >= fundraiser_data.duration {
+        return Err(FundraiserError::DeadlineReached.into());
+    }
 
     // transfer the contribution amount to the vault
     Transfer {
@@ -70,8 +177,13 @@ pub fn contribute(
         amount
     }.invoke()?;
 
-    fundraiser_data.current_amount = fundraiser_data.current_amount + amount;
-    contributor_data.amount = contributor_data.amount + amount;
+    fundraiser_data.current_amount = current_amount;
+    contributor_data.amount = contributed;
+
+    // the contribution that completes the goal closes the campaign to further contributions
+    if current_amount == fundraiser_data.amount_to_raise {
+        fundraiser_data.status = FundraiserStatus::GoalReached as u8;
+    }
 
     fundraiser.try_borrow_mut_data()?.copy_from_slice(bytemuck::bytes_of(&fundraiser_data));
     contributor_account.try_borrow_mut_data()?.copy_from_slice(bytemuck::bytes_of(&contributor_data));
@@ -1,14 +1,18 @@
-use crate::state::Fundraiser;
+use crate::state::{Fundraiser, FundraiserStatus};
 use pinocchio::{
     program_error::ProgramError,
     ProgramResult,
     account_info::AccountInfo,
     sysvars::rent::Rent,
+    sysvars::clock::Clock,
     sysvars::Sysvar,
     instruction::{Seed, Signer},
 };
 use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token::{instructions::InitializeAccount3, state::TokenAccount};
 
+use crate::constants::{BPS_SCALER, MAX_CONTRIBUTION_BPS, MIN_AMOUNT_TO_RAISE};
+use crate::error::FundraiserError;
 use crate::processor::InitializeArgs;
 use crate::utils::validate_pda;
 pub fn initialize(
@@ -18,33 +22,74 @@ pub fn initialize(
     let [
         maker, // person who is starting the fundraiser (signer)
         mint_to_raise, // the mint that the maker wants to receive
-        fundraiser, // the PDA that will hold the fundraiser data (seeds: [b"fundraiser", maker.key().as_ref()] + bump, payer: maker, space)
+        fundraiser, // the PDA that will hold the fundraiser data (seeds: [b"fundraiser", maker.key().as_ref(), campaign_id.to_le_bytes()] + bump, payer: maker, space)
+        vault, // the PDA token account for mint_to_raise that will hold the contributions (seeds: [b"vault", fundraiser.key().as_ref()] + bump), its own authority
         _system_program, 
+        _token_program,
     ] = accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys)
     };
 
-    assert!(maker.is_signer());
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
     let InitializeArgs {
         amount, // the target amount that the maker is trying to raise
-        duration, // the timeframe to collect all the contributions (in days)
+        duration, // the timeframe to collect all the contributions (in seconds)
         fundraiser_bump, // since our Fundraiser account will be a PDA (Program Derived Address), we will pass the bump of the account
+        min_contribution, // limits per contribution and per contributor, 0 falls back to the defaults in constants
+        max_contribution_bps,
+        max_contribution,
+        campaign_id, // tells apart the campaigns of the same maker
+        vault_bump,
     } = InitializeArgs::try_from(args)?;
 
+    // a campaign without time to contribute is a mistake
+    if duration <= 0 {
+        return Err(FundraiserError::InvalidDuration.into());
+    }
+
+    // a campaign with nothing to raise is a mistake
+    if amount == 0 {
+        return Err(FundraiserError::InvalidGoal.into());
+    }
+
+    let min_contribution = if min_contribution == 0 { MIN_AMOUNT_TO_RAISE } else { min_contribution };
+    let max_contribution_bps = if max_contribution_bps == 0 { MAX_CONTRIBUTION_BPS } else { max_contribution_bps };
+
+    // a campaign nobody can contribute to is a mistake, the per-wallet share of the goal
+    // is computed like in contribute and has to leave room for the minimum as well
+    let amount_allowed = (amount as u128 * max_contribution_bps as u128 / BPS_SCALER as u128) as u64;
+    if max_contribution_bps > BPS_SCALER
+        || amount_allowed < min_contribution
+        || (max_contribution != 0 && max_contribution < min_contribution)
+    {
+        return Err(FundraiserError::InvalidContributionLimits.into());
+    }
+
     // validate the PDA
     let bump = fundraiser_bump as u8;
     let fundraiser_bump_bytes = bump.to_le_bytes();
+    let campaign_id_bytes = campaign_id.to_le_bytes();
     validate_pda(
-        &[b"fundraiser", maker.key().as_ref(), &fundraiser_bump_bytes],
+        &[b"fundraiser", maker.key().as_ref(), &campaign_id_bytes, &fundraiser_bump_bytes],
         &crate::ID,
         fundraiser.key()
-    );
+    )?;
+
+    let vault_bump_bytes = (vault_bump as u8).to_le_bytes();
+    validate_pda(
+        &[b"vault", fundraiser.key().as_ref(), &vault_bump_bytes],
+        &crate::ID,
+        vault.key()
+    )?;
 
     let signer_seeds = [
         Seed::from(b"fundraiser"), 
         Seed::from(maker.key().as_ref()), 
+        Seed::from(campaign_id_bytes.as_ref()),
         Seed::from(fundraiser_bump_bytes.as_ref())
     ];
     let signer = [Signer::from(&signer_seeds)];
@@ -67,6 +112,30 @@ pub fn initialize(
     .invoke_signed(&signer)?;
     
 
+    // create the vault, checker and refund sign for it with its own seeds
+    let vault_signer_seeds = [
+        Seed::from(b"vault"),
+        Seed::from(fundraiser.key().as_ref()),
+        Seed::from(vault_bump_bytes.as_ref())
+    ];
+    let vault_signer = [Signer::from(&vault_signer_seeds)];
+
+    CreateAccount{
+        from: maker,
+        to: vault,
+        lamports: rent.minimum_balance(TokenAccount::LEN),
+        space: TokenAccount::LEN as u64,
+        owner: &pinocchio_token::ID,
+    }
+    .invoke_signed(&vault_signer)?;
+
+    InitializeAccount3{
+        account: vault,
+        mint: mint_to_raise,
+        owner: vault.key(),
+    }
+    .invoke()?;
+
     // assign fundraiser data
     let mut binding = fundraiser.try_borrow_mut_data()?;
         
@@ -80,9 +149,16 @@ pub fn initialize(
         mint_to_raise: *mint_to_raise.key(),
         amount_to_raise: amount,
         current_amount: 0,
-        time_started: 0,
+        time_started: Clock::get()?.unix_timestamp,
         duration,
         bump: fundraiser_bump,
+        campaign_id,
+        vault_bump,
+        min_contribution,
+        max_contribution_bps,
+        max_contribution,
+        status: FundraiserStatus::Active as u8,
+        _padding: [0; 7],
     };
 
     Ok(())
@@ -2,8 +2,10 @@ mod initialize;
 mod contribute;
 mod checker;
 mod refund;
+mod cancel;
 
 pub use initialize::*;
 pub use contribute::*;
 pub use checker::*;
-pub use refund::*;
\ No newline at end of file
+pub use refund::*;
+pub use cancel::*;
\ No newline at end of file
@@ -1,35 +1,90 @@
 use pinocchio::{
     account_info::AccountInfo, 
+    instruction::{Seed, Signer},
     program_error::ProgramError, 
     sysvars::{clock::Clock, Sysvar}, 
     ProgramResult
 };
-use crate::state::{ Fundraiser, Contributor };
+use crate::error::FundraiserError;
+use crate::state::{ Fundraiser, FundraiserStatus, Contributor };
+use crate::utils::{close_program_account, validate_pda, validate_vault_mint};
 use pinocchio_token::instructions::Transfer;
 
-pub fn refund(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+pub fn refund(accounts: &[AccountInfo]) -> ProgramResult {
 
     let [ 
-        _contributor,
+        contributor,
         fundraiser,
         contributor_ta,
-        contributor_account, // account that saves the contribution amount of the contributor
-        vault, // account that receives the contribution
+        contributor_account, // account that saves the contribution amount of the contributor, closed back to the contributor
+        vault, // account that holds the contributions
         _token_program,
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    let current_time = Clock::get()?.unix_timestamp; 
-    
-    let fundraiser_data: Fundraiser = *bytemuck::try_from_bytes::<Fundraiser>(&fundraiser.try_borrow_mut_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
-    let contributor_data: Contributor = *bytemuck::try_from_bytes::<Contributor>(&contributor_account.try_borrow_mut_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+    if !contributor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-    // make sure the fundraiser duration has been reached
-    assert!(fundraiser_data.duration <= (current_time - fundraiser_data.time_started)); // TODO: make sure to save and convert the time correctly
+    if fundraiser.owner() != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let mut fundraiser_data: Fundraiser = *bytemuck::try_from_bytes::<Fundraiser>(&fundraiser.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
 
-    // make sure the fundraising goal has not been reached
-    assert!(fundraiser_data.current_amount < fundraiser_data.amount_to_raise);
+    // check if fundraiser is correct
+    validate_pda(&[
+        b"fundraiser",
+        fundraiser_data.maker.as_ref(),
+        &fundraiser_data.campaign_id.to_le_bytes(),
+        &[fundraiser_data.bump as u8]
+    ], &crate::ID, fundraiser.key())?;
+
+    // a cancelled or failed fundraiser refunds, a cancelled one at once whatever the time or the amount raised
+    let current_time = Clock::get()?.unix_timestamp;
+    let status = fundraiser_data.status_at(current_time)?;
+    match status {
+        FundraiserStatus::Cancelled | FundraiserStatus::Failed => {}
+        // make sure the fundraiser duration has been reached
+        FundraiserStatus::Active if current_time - fundraiser_data.time_started < fundraiser_data.duration => {
+            return Err(FundraiserError::DeadlineNotReached.into());
+        }
+        // an active fundraiser past its deadline that hasn't failed has raised its goal
+        FundraiserStatus::Active | FundraiserStatus::GoalReached => return Err(FundraiserError::GoalReached.into()),
+    }
+
+    // check if vault is correct
+    let u8_vault_bump = fundraiser_data.vault_bump as u8;
+    let vault_bump_bytes = u8_vault_bump.to_le_bytes();
+    validate_pda(&[
+        b"vault",
+        fundraiser.key().as_ref(),
+        &vault_bump_bytes
+    ], &crate::ID, vault.key())?;
+    validate_vault_mint(vault, &fundraiser_data.mint_to_raise)?;
+
+    // check if contributor account is correct, it has to belong to the signer
+    if contributor_account.owner() != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let contributor_data: Contributor = *bytemuck::try_from_bytes::<Contributor>(&contributor_account.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+    let u8_contributor_bump = contributor_data.contributor_bump as u8;
+    validate_pda(&[
+        b"contributor",
+        fundraiser.key().as_ref(),
+        contributor.key().as_ref(),
+        &[u8_contributor_bump]
+    ], &crate::ID, contributor_account.key())?;
+
+    // a contributor can't take back more than the fundraiser has on record
+    let current_amount = fundraiser_data.current_amount.checked_sub(contributor_data.amount).ok_or(FundraiserError::InvalidContributorAmount)?;
+
+    let signer_seeds = [
+        Seed::from(b"vault"), 
+        Seed::from(fundraiser.key().as_ref()), 
+        Seed::from(vault_bump_bytes.as_ref())
+    ];
+    let signer = Signer::from(&signer_seeds);
 
     // transfer the contribution amount back to the contributor
     Transfer {
@@ -37,9 +92,15 @@ pub fn refund(accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
         to: contributor_ta,
         authority: vault,
         amount: contributor_data.amount
-    }.invoke()?;
+    }.invoke_signed(&[signer])?;
+
+    // a failure that no instruction has written back yet is stored with the refund
+    fundraiser_data.status = status as u8;
+    fundraiser_data.current_amount = current_amount;
+    fundraiser.try_borrow_mut_data()?.copy_from_slice(bytemuck::bytes_of(&fundraiser_data));
 
-    // TODO:close vault and fundraiser if vault is empty?
+    // closing the contributor account makes the refund impossible to replay
+    close_program_account(contributor_account, contributor)?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -8,6 +8,7 @@ mod state;
 mod tests;
 mod utils;
 mod constants;
+mod error;
 
 pub const ID: [u8; 32] =
     five8_const::decode_32_const("22222222222222222222222222222222222222222222");
@@ -13,6 +13,7 @@ pub enum FundraiserInstructions {
     Contribute,
     Checker,
     Refund,
+    Cancel,
 }
 
 impl TryFrom<&u8> for FundraiserInstructions {
@@ -24,6 +25,7 @@ impl TryFrom<&u8> for FundraiserInstructions {
             1 => Ok(Self::Contribute),
             2 => Ok(Self::Checker),
             3 => Ok(Self::Refund),
+            4 => Ok(Self::Cancel),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -33,8 +35,13 @@ impl TryFrom<&u8> for FundraiserInstructions {
 #[derive(Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
 pub struct InitializeArgs {
     pub amount: u64,
-    pub duration: i64,
+    pub duration: i64, // in seconds
     pub fundraiser_bump: u64,
+    pub min_contribution: u64, // 0 for MIN_AMOUNT_TO_RAISE
+    pub max_contribution_bps: u64, // 0 for MAX_CONTRIBUTION_BPS
+    pub max_contribution: u64, // 0 for no absolute per-wallet cap
+    pub campaign_id: u64,
+    pub vault_bump: u64,
 }
 
 impl TryFrom<&[u8]> for InitializeArgs {
@@ -51,7 +58,7 @@ impl TryFrom<&[u8]> for InitializeArgs {
 #[derive(Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
 pub struct ContributeArgs {
     pub amount: u64,
-    pub vault_bump: u64,
+    pub contributor_bump: u64,
 }
 
 impl TryFrom<&[u8]> for ContributeArgs {
@@ -64,40 +71,6 @@ impl TryFrom<&[u8]> for ContributeArgs {
     }
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
-pub struct CheckerArgs {
-    pub fundraiser_bump: u64,
-    pub vault_bump: u64,
-}
-
-impl TryFrom<&[u8]> for CheckerArgs {
-    
-    type Error = ProgramError;
-    
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        bytemuck::try_pod_read_unaligned::<Self>(data)
-            .map_err(|_| ProgramError::InvalidInstructionData)
-    }
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
-pub struct RefundArgs {
-    pub fundraiser_bump: u64,
-    pub vault_bump: u64,
-}
-
-impl TryFrom<&[u8]> for RefundArgs {
-    
-    type Error = ProgramError;
-    
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        bytemuck::try_pod_read_unaligned::<Self>(data)
-            .map_err(|_| ProgramError::InvalidInstructionData)
-    }
-}
-
 pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
 
     if program_id.ne(&crate::ID) {
@@ -109,7 +82,8 @@ pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instru
     match FundraiserInstructions::try_from(discriminator)? {
         FundraiserInstructions::Initialize => initialize(accounts, data),
         FundraiserInstructions::Contribute => contribute(accounts, data),
-        FundraiserInstructions::Checker => checker(accounts, data),
-        FundraiserInstructions::Refund => refund(accounts, data)
+        FundraiserInstructions::Checker => checker(accounts),
+        FundraiserInstructions::Refund => refund(accounts),
+        FundraiserInstructions::Cancel => cancel(accounts),
     }
 }
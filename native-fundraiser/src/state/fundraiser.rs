@@ -1,6 +1,30 @@
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
 use bytemuck::{Pod, Zeroable};
 
+// there is no Withdrawn status, checker pays out the maker and closes the fundraiser in the same instruction
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FundraiserStatus {
+    Active, // accepts contributions until the deadline
+    GoalReached, // raised amount_to_raise, the maker can withdraw
+    Failed, // deadline passed short of the goal, see Fundraiser::status_at
+    Cancelled, // abandoned by the maker, contributors can refund at once
+}
+
+impl TryFrom<u8> for FundraiserStatus {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Active),
+            1 => Ok(Self::GoalReached),
+            2 => Ok(Self::Failed),
+            3 => Ok(Self::Cancelled),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct Fundraiser {
@@ -8,11 +32,36 @@ pub struct Fundraiser {
     pub mint_to_raise: Pubkey,
     pub amount_to_raise: u64,
     pub current_amount: u64,
-    pub time_started: i64,
-    pub duration: i64,
+    pub time_started: i64, // unix timestamp of initialize
+    pub duration: i64, // in seconds, contributions are accepted until time_started + duration
     pub bump: u64,
+    pub campaign_id: u64, // chosen by the maker, part of the PDA seeds so a maker can run several campaigns
+    pub vault_bump: u64, // bump of the vault (seeds: [b"vault", fundraiser]), the vault is its own token authority
+    pub min_contribution: u64, // smallest accepted contribution, in base units of mint_to_raise
+    pub max_contribution_bps: u64, // per-wallet cap as a share of amount_to_raise, in basis points
+    pub max_contribution: u64, // absolute per-wallet cap in base units, 0 if there is none
+    pub status: u8, // FundraiserStatus
+    pub _padding: [u8; 7],
 }
 
 impl Fundraiser {
     pub const LEN: usize = std::mem::size_of::<Fundraiser>();
+
+    pub fn status(&self) -> Result<FundraiserStatus, ProgramError> {
+        FundraiserStatus::try_from(self.status)
+    }
+
+    // the status at `now`, an active fundraiser has failed from its deadline on if it is short of the goal,
+    // whether or not an instruction has written Failed back yet
+    pub fn status_at(&self, now: i64) -> Result<FundraiserStatus, ProgramError> {
+        let status = self.status()?;
+        if status == FundraiserStatus::Active
+            && now - self.time_started >= self.duration
+            && self.current_amount < self.amount_to_raise
+        {
+            return Ok(FundraiserStatus::Failed);
+        }
+
+        Ok(status)
+    }
 }
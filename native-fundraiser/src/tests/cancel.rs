@@ -0,0 +1,132 @@
+use mollusk_svm::{
+    result::ProgramResult,
+    Mollusk,
+};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount}, 
+    instruction::{AccountMeta, Instruction}, 
+    program_error::ProgramError,
+    pubkey::Pubkey as SolanaPubkey
+};
+use bytemuck::from_bytes;
+
+use crate::error::FundraiserError;
+use crate::state::{Fundraiser, FundraiserStatus};
+
+use crate::tests::{
+    setup_mollusk,
+    create_initialized_fundraiser_account,
+    fundraiser_error,
+    set_fundraiser_status,
+    CAMPAIGN_ID,
+    DURATION,
+    PROGRAM_ID
+};
+
+// account positions in the cancel instruction
+const MAKER: usize = 0;
+const FUNDRAISER: usize = 1;
+
+/**
+ * Sets up the cancellation of an active fundraiser that raised 100_000
+ * 
+ * @param mollusk: The mollusk instance
+ * @returns: The cancel instruction and its accounts
+ */
+fn setup_cancel(mollusk: &Mollusk) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
+    let maker = SolanaPubkey::new_unique();
+    let mint_to_raise = SolanaPubkey::new_unique();
+    let (fundraiser, fundraiser_bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref(), &CAMPAIGN_ID.to_le_bytes()], &PROGRAM_ID);
+    let fundraiser_account = create_initialized_fundraiser_account(maker, mollusk, mint_to_raise, 100_000, 0, fundraiser_bump as u64);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[4],
+        vec![
+            AccountMeta::new(maker, true),
+            AccountMeta::new(fundraiser, false),
+        ],
+    );
+
+    let accounts = vec![
+        (maker, AccountSharedData::new(1_000_000_000, 0, &SolanaPubkey::default())),
+        (fundraiser, fundraiser_account),
+    ];
+
+    (instruction, accounts)
+}
+
+#[test]
+fn cancel(){
+    let mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_cancel(&mollusk);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    // the fundraiser stays open with its funds, only the status changes
+    let fundraiser = result.get_account(&instruction.accounts[FUNDRAISER].pubkey).expect("Failed to get fundraiser account");
+    let fundraiser_data = from_bytes::<Fundraiser>(fundraiser.data());
+    assert_eq!(fundraiser_data.status, FundraiserStatus::Cancelled as u8);
+    assert_eq!(fundraiser_data.current_amount, 100_000);
+}
+
+#[test]
+fn cancel_requires_maker_signature(){
+    let mollusk = setup_mollusk();
+    let (mut instruction, accounts) = setup_cancel(&mollusk);
+
+    instruction.accounts[MAKER].is_signer = false;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::MissingRequiredSignature));
+}
+
+#[test]
+fn cancel_by_someone_else(){
+    let mollusk = setup_mollusk();
+    let (mut instruction, mut accounts) = setup_cancel(&mollusk);
+
+    let other = SolanaPubkey::new_unique();
+    instruction.accounts[MAKER].pubkey = other;
+    accounts[MAKER].0 = other;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidPda));
+}
+
+#[test]
+fn cancel_twice(){
+    let mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_cancel(&mollusk);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let result = mollusk.process_instruction(&instruction, &result.resulting_accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
+
+#[test]
+fn cancel_after_goal_reached(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_cancel(&mollusk);
+
+    // a successful campaign can only be withdrawn
+    set_fundraiser_status(&mut accounts[FUNDRAISER].1, FundraiserStatus::GoalReached);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
+
+#[test]
+fn cancel_after_deadline(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_cancel(&mollusk);
+
+    // the campaign failed short of its goal, contributors refund without a cancel
+    mollusk.sysvars.clock.unix_timestamp = DURATION;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
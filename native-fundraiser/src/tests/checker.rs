@@ -1,51 +1,58 @@
 use mollusk_svm::{
     program,
     result::ProgramResult,
+    Mollusk,
 };
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount}, 
     instruction::{AccountMeta, Instruction}, 
+    program_error::ProgramError,
     program_pack::Pack, 
     pubkey::Pubkey as SolanaPubkey
 };
 
+use crate::error::FundraiserError;
+use crate::state::{Fundraiser, FundraiserStatus};
+
 use crate::tests::{
     setup_mollusk,
     create_initialized_fundraiser_account,
     create_initialized_ata_account,
     create_initialized_vault_account,
+    fundraiser_error,
+    set_fundraiser_status,
+    CAMPAIGN_ID,
+    DURATION,
     PROGRAM_ID
 };
 
-#[test]
-fn checker(){
-    let mollusk = setup_mollusk();
+/**
+ * Sets up a checker call on a fundraiser that holds `current_amount` in its vault
+ * 
+ * @param mollusk: The mollusk instance
+ * @param current_amount: The amount raised by the fundraiser
+ * @param status: The status of the fundraiser
+ * @returns: The checker instruction and its accounts
+ */
+fn setup_checker(mollusk: &Mollusk, current_amount: u64, status: FundraiserStatus) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
     let maker = SolanaPubkey::new_unique();
     let mint_to_raise = SolanaPubkey::new_unique();
-    let (fundraiser, fundraiser_bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref()], &PROGRAM_ID);
+    let (fundraiser, fundraiser_bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref(), &CAMPAIGN_ID.to_le_bytes()], &PROGRAM_ID);
     let (token_program, token_program_account) = (
         spl_token::ID,
         program::create_program_account_loader_v3(&spl_token::ID),
     );
-    let (vault, vault_bump) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
+    let (vault, _) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
     let maker_ata = SolanaPubkey::new_unique();
-    let fundraiser_account = create_initialized_fundraiser_account(maker, &mollusk, mint_to_raise, 1_000_000, 0, fundraiser_bump as u64);
-
-    let vault_account = create_initialized_vault_account(vault, mint_to_raise, &mollusk,1_000_000);
-    let maker_ata_account = create_initialized_ata_account(maker, &mollusk, mint_to_raise, 1_000_000_000);
+    let mut fundraiser_account = create_initialized_fundraiser_account(maker, mollusk, mint_to_raise, current_amount, 0, fundraiser_bump as u64);
+    set_fundraiser_status(&mut fundraiser_account, status);
 
-    let fundraiser_bump_u64 = fundraiser_bump as u64;
-    let vault_bump_u64 = vault_bump as u64;
-    let data = [
-        vec![2],
-        fundraiser_bump_u64.to_le_bytes().to_vec(),
-        vault_bump_u64.to_le_bytes().to_vec(),
-    ]
-    .concat();
+    let vault_account = create_initialized_vault_account(vault, mint_to_raise, mollusk, current_amount);
+    let maker_ata_account = create_initialized_ata_account(maker, mollusk, mint_to_raise, 1_000_000_000);
 
     let instruction = Instruction::new_with_bytes(
         PROGRAM_ID,
-        &data,
+        &[2],
         vec![
             AccountMeta::new(maker, true),
             AccountMeta::new(fundraiser, false),
@@ -55,24 +62,119 @@ fn checker(){
         ],
     );
 
-    let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
-        &instruction,
-        &vec![
-            (maker, AccountSharedData::new(1_000_000_000, 0, &SolanaPubkey::default())),
-            (fundraiser, fundraiser_account),
-            (vault, vault_account),
-            (maker_ata, maker_ata_account),
-            (token_program, token_program_account),
-        ],
+    let accounts = vec![
+        (maker, AccountSharedData::new(1_000_000_000, 0, &SolanaPubkey::default())),
+        (fundraiser, fundraiser_account),
+        (vault, vault_account),
+        (maker_ata, maker_ata_account),
+        (token_program, token_program_account),
+    ];
+
+    (instruction, accounts)
+}
+
+#[test]
+fn checker(){
+    let mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_checker(&mollusk, 1_000_000, FundraiserStatus::GoalReached);
+    let maker = instruction.accounts[0].pubkey;
+    let fundraiser = instruction.accounts[1].pubkey;
+    let vault = instruction.accounts[2].pubkey;
+    let maker_ata = instruction.accounts[3].pubkey;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let maker_ata_account = result.get_account(&maker_ata).expect("Failed to get maker ata");
+    assert_eq!(spl_token::state::Account::unpack(maker_ata_account.data()).unwrap().amount, 1_001_000_000);
+
+    // vault and fundraiser are closed, their rent goes back to the maker
+    let vault_account = result.get_account(&vault).expect("Failed to get vault account");
+    assert_eq!(vault_account.lamports(), 0);
+    let fundraiser_account = result.get_account(&fundraiser).expect("Failed to get fundraiser account");
+    assert_eq!(fundraiser_account.lamports(), 0);
+    assert!(fundraiser_account.data().is_empty());
+
+    let maker_account = result.get_account(&maker).expect("Failed to get maker account");
+    assert_eq!(
+        maker_account.lamports(),
+        1_000_000_000
+            + mollusk.sysvars.rent.minimum_balance(spl_token::state::Account::LEN)
+            + mollusk.sysvars.rent.minimum_balance(Fundraiser::LEN)
     );
+
+    // a finished fundraiser can't be checked again, the closed account has no data left
+    let result = mollusk.process_instruction(&instruction, &result.resulting_accounts);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn checker_with_tokens_sent_to_vault(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_checker(&mollusk, 1_000_000, FundraiserStatus::GoalReached);
+    let vault = instruction.accounts[2].pubkey;
+    let maker_ata = instruction.accounts[3].pubkey;
+
+    // someone transferred dust to the vault directly, it isn't part of current_amount
+    let mint_to_raise = SolanaPubkey::new_from_array(bytemuck::pod_read_unaligned::<Fundraiser>(accounts[1].1.data()).mint_to_raise);
+    accounts[2].1 = create_initialized_vault_account(vault, mint_to_raise, &mollusk, 1_000_005);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
     assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
 
-    let vault = result.get_account(&vault).expect("Failed to get vault account");
+    // the maker gets the dust too and the vault can still be closed
+    let maker_ata_account = result.get_account(&maker_ata).expect("Failed to get maker ata");
+    assert_eq!(spl_token::state::Account::unpack(maker_ata_account.data()).unwrap().amount, 1_001_000_005);
+    let vault_account = result.get_account(&vault).expect("Failed to get vault account");
+    assert_eq!(vault_account.lamports(), 0);
+}
+
+#[test]
+fn checker_before_goal_reached(){
+    let mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_checker(&mollusk, 999_999, FundraiserStatus::Active);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::GoalNotReached));
+}
+
+#[test]
+fn checker_requires_maker_signature(){
+    let mollusk = setup_mollusk();
+    let (mut instruction, accounts) = setup_checker(&mollusk, 1_000_000, FundraiserStatus::GoalReached);
+
+    instruction.accounts[0].is_signer = false;
 
-    let vault_data = vault.data();
-    let vault_account_info = spl_token::state::Account::unpack(&vault_data).unwrap();
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::MissingRequiredSignature));
+}
 
-    assert_eq!(vault_account_info.amount, 0);
+#[test]
+fn checker_after_cancel(){
+    let mollusk = setup_mollusk();
+    // the maker gave up the campaign, the funds are the contributors' again
+    let (instruction, accounts) = setup_checker(&mollusk, 1_000_000, FundraiserStatus::Cancelled);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
+
+#[test]
+fn checker_after_failed(){
+    let mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_checker(&mollusk, 400_000, FundraiserStatus::Failed);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
+
+#[test]
+fn checker_after_deadline_short_of_goal(){
+    let mut mollusk = setup_mollusk();
+    // still Active in the account, but the campaign failed at its deadline
+    let (instruction, accounts) = setup_checker(&mollusk, 400_000, FundraiserStatus::Active);
+    mollusk.sysvars.clock.unix_timestamp = DURATION;
 
-    // TODO: check if maker_ata has the correct amount
-}
\ No newline at end of file
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
@@ -1,26 +1,55 @@
-use mollusk_svm::program;
+use mollusk_svm::{
+    program,
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
+};
 use solana_sdk::{
-    account::{AccountSharedData, ReadableAccount}, 
+    account::{AccountSharedData, ReadableAccount, WritableAccount}, 
     instruction::{AccountMeta, Instruction},
     program_pack::Pack, 
     pubkey::Pubkey as SolanaPubkey
 };
-use bytemuck::bytes_of;
+use bytemuck::{bytes_of, from_bytes, pod_read_unaligned};
 
-use crate::state::Contributor;
+use crate::error::FundraiserError;
+use crate::state::{Contributor, Fundraiser, FundraiserStatus};
 
 use crate::tests::{
     setup_mollusk,
     create_initialized_fundraiser_account,
+    create_initialized_contributor_account,
     create_initialized_ata_account,
     create_initialized_vault_account,
+    fundraiser_error,
+    set_fundraiser_status,
+    AMOUNT_TO_RAISE,
+    CAMPAIGN_ID,
+    DURATION,
     PROGRAM_ID
 };
 
-#[test]
-fn contribute(){
-    let mollusk = setup_mollusk();
+// account positions in the contribute instruction
+const FUNDRAISER: usize = 1;
+const CONTRIBUTOR_ACCOUNT: usize = 3;
+const VAULT: usize = 4;
 
+/**
+ * Sets up a contribution of `amount` to a fundraiser with the default limits that started at `time_started`
+ * 
+ * @param mollusk: The mollusk instance
+ * @param amount: The amount to contribute
+ * @param current_amount: The amount raised by the fundraiser before
+ * @param time_started: The time the fundraiser started
+ * @param contributed: The amount contributed before, None if the contributor PDA doesn't exist yet
+ * @returns: The contribute instruction and its accounts
+ */
+fn setup_contribute(
+    mollusk: &Mollusk,
+    amount: u64,
+    current_amount: u64,
+    time_started: i64,
+    contributed: Option<u64>,
+) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
     // the maker is the one raising the funds
     let maker = SolanaPubkey::new_unique();
 
@@ -28,34 +57,28 @@ fn contribute(){
     let mint_to_raise = SolanaPubkey::new_unique();
 
     // create the fundraiser account that holds the state of the fundraiser
-    let (fundraiser, bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref()], &PROGRAM_ID);
-    let fundraiser_account = create_initialized_fundraiser_account(maker, &mollusk, mint_to_raise, 0, 0, bump as u64);
+    let (fundraiser, bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref(), &CAMPAIGN_ID.to_le_bytes()], &PROGRAM_ID);
+    let fundraiser_account = create_initialized_fundraiser_account(maker, mollusk, mint_to_raise, current_amount, time_started, bump as u64);
 
     // The person who wants to contribute to the fundraiser
     let contributor = SolanaPubkey::new_unique();
-    let contributor_account_data = AccountSharedData::new(1_000_000_000, 0, &contributor);
+    let contributor_account_data = AccountSharedData::new(1_000_000_000, 0, &SolanaPubkey::default());
 
     // The contributor's token account
     let contributor_ata = SolanaPubkey::new_unique();
-    let contributor_ata_account = create_initialized_ata_account(contributor, &mollusk, mint_to_raise, 1_000_000_000);
+    let contributor_ata_account = create_initialized_ata_account(contributor, mollusk, mint_to_raise, 1_000_000_000);
 
-    // The PDA that holds the state of the contributor
-    let (contributor_pda, contributor_bump) = SolanaPubkey::find_program_address(&[b"contributor", contributor.as_ref()], &PROGRAM_ID);
+    // The PDA that holds the state of the contributor, created by contribute on the first contribution
+    let (contributor_pda, contributor_bump) = SolanaPubkey::find_program_address(&[b"contributor", fundraiser.as_ref(), contributor.as_ref()], &PROGRAM_ID);
     let contributor_bump_u64 = contributor_bump as u64;
-    let mut contributor_account = AccountSharedData::new(
-        mollusk.sysvars.rent.minimum_balance(Contributor::LEN),
-        Contributor::LEN,
-        &PROGRAM_ID,
-    );
-
-    contributor_account.set_data_from_slice(bytes_of::<Contributor>(&Contributor {
-        amount: 0,
-        contributor_bump: contributor_bump_u64,
-    }));
+    let contributor_account = match contributed {
+        Some(amount) => create_initialized_contributor_account(mollusk, amount, contributor_bump_u64),
+        None => AccountSharedData::new(0, 0, &SolanaPubkey::default()),
+    };
 
     // The vault that holds the funds raised
-    let (vault, vault_bump) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
-    let vault_account = create_initialized_vault_account(fundraiser, mint_to_raise, &mollusk,0);
+    let (vault, _) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
+    let vault_account = create_initialized_vault_account(vault, mint_to_raise, mollusk, 0);
 
     // The token program
     let (token_program, token_program_account) = (
@@ -63,11 +86,12 @@ fn contribute(){
         program::create_program_account_loader_v3(&spl_token::ID),
     );        
 
-    let bump_u64 = vault_bump as u64;
+    let (system_program, system_program_account) = program::keyed_account_for_system_program();
+
     let data = [
         vec![1],
-        1_000u64.to_le_bytes().to_vec(), // amount
-        bump_u64.to_le_bytes().to_vec(), // vault bump
+        amount.to_le_bytes().to_vec(), // amount
+        contributor_bump_u64.to_le_bytes().to_vec(), // contributor bump
     ]
     .concat();
 
@@ -82,20 +106,74 @@ fn contribute(){
             AccountMeta::new(contributor_pda, false),
             AccountMeta::new(vault, false),
             AccountMeta::new(token_program, false),
+            AccountMeta::new(system_program, false),
         ],
     );
 
-    let result = mollusk.process_instruction(
-        &instruction,
-        &vec![
-            (contributor, contributor_account_data),
-            (fundraiser, fundraiser_account),
-            (contributor_ata, contributor_ata_account),
-            (contributor_pda, contributor_account),
-            (vault, vault_account),
-            (token_program, token_program_account),
-        ],
-    );
+    let accounts = vec![
+        (contributor, contributor_account_data),
+        (fundraiser, fundraiser_account),
+        (contributor_ata, contributor_ata_account),
+        (contributor_pda, contributor_account),
+        (vault, vault_account),
+        (token_program, token_program_account),
+        (system_program, system_program_account),
+    ];
+
+    (instruction, accounts)
+}
+
+/**
+ * Contributes `amount` to a fundraiser with the default limits that started at `time_started`
+ * 
+ * @param mollusk: The mollusk instance, with the clock set to the time of the contribution
+ * @param amount: The amount to contribute
+ * @param current_amount: The amount raised by the fundraiser before
+ * @param time_started: The time the fundraiser started
+ * @param contributed: The amount contributed before, None if the contributor PDA doesn't exist yet
+ * @returns: The result of the contribute instruction, the vault and the contributor PDA
+ */
+fn process_contribute(
+    mollusk: &Mollusk,
+    amount: u64,
+    current_amount: u64,
+    time_started: i64,
+    contributed: Option<u64>,
+) -> (InstructionResult, SolanaPubkey, SolanaPubkey) {
+    let (instruction, accounts) = setup_contribute(mollusk, amount, current_amount, time_started, contributed);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+
+    (result, accounts[VAULT].0, accounts[CONTRIBUTOR_ACCOUNT].0)
+}
+
+/**
+ * Overrides the contribution limits of the fundraiser in `accounts`
+ * 
+ * @param accounts: The contribute accounts
+ * @param min_contribution: The smallest accepted contribution
+ * @param max_contribution_bps: The per-wallet cap in basis points of the goal
+ * @param max_contribution: The absolute per-wallet cap, 0 for none
+ */
+fn set_contribution_limits(
+    accounts: &mut [(SolanaPubkey, AccountSharedData)],
+    min_contribution: u64,
+    max_contribution_bps: u64,
+    max_contribution: u64,
+) {
+    let mut fundraiser_data = pod_read_unaligned::<Fundraiser>(accounts[FUNDRAISER].1.data());
+    fundraiser_data.min_contribution = min_contribution;
+    fundraiser_data.max_contribution_bps = max_contribution_bps;
+    fundraiser_data.max_contribution = max_contribution;
+    accounts[FUNDRAISER].1.set_data_from_slice(bytes_of(&fundraiser_data));
+}
+
+#[test]
+fn contribute(){
+    let mollusk = setup_mollusk();
+
+    let (result, vault, contributor_pda) = process_contribute(&mollusk, 1_000, 0, 0, Some(0));
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
 
     let vault = result.get_account(&vault).expect("Failed to get vault account");
 
@@ -104,6 +182,237 @@ fn contribute(){
 
     assert_eq!(vault_account_info.amount, 1_000);
 
-    // TODO: check if contributer account safed correct amount
+    let contributor_account = result.get_account(&contributor_pda).expect("Failed to get contributor account");
+    assert_eq!(from_bytes::<Contributor>(contributor_account.data()).amount, 1_000);
+}
+
+#[test]
+fn first_contribution_creates_contributor_account(){
+    let mollusk = setup_mollusk();
+
+    let (result, _, contributor_pda) = process_contribute(&mollusk, 1_000, 0, 0, None);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let contributor_account = result.get_account(&contributor_pda).expect("Failed to get contributor account");
+    assert_eq!(contributor_account.owner(), &PROGRAM_ID);
+    assert_eq!(contributor_account.lamports(), mollusk.sysvars.rent.minimum_balance(Contributor::LEN));
+    assert_eq!(from_bytes::<Contributor>(contributor_account.data()).amount, 1_000);
+}
+
+#[test]
+fn first_contribution_to_prefunded_contributor_account(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, None);
+
+    // someone sent lamports to the contributor PDA before the first contribution
+    accounts[CONTRIBUTOR_ACCOUNT].1 = AccountSharedData::new(1_000, 0, &SolanaPubkey::default());
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    // topped up to rent exemption and taken over by the program
+    let contributor_account = result.get_account(&accounts[CONTRIBUTOR_ACCOUNT].0).expect("Failed to get contributor account");
+    assert_eq!(contributor_account.owner(), &PROGRAM_ID);
+    assert_eq!(contributor_account.lamports(), mollusk.sysvars.rent.minimum_balance(Contributor::LEN));
+    assert_eq!(from_bytes::<Contributor>(contributor_account.data()).amount, 1_000);
+}
+
+#[test]
+fn first_contribution_with_non_canonical_bump(){
+    let mollusk = setup_mollusk();
+    let (mut instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, None);
+
+    // the next bump below the canonical one that is off the curve too
+    let contributor = accounts[0].0;
+    let fundraiser = accounts[FUNDRAISER].0;
+    let (_, canonical_bump) = SolanaPubkey::find_program_address(&[b"contributor", fundraiser.as_ref(), contributor.as_ref()], &PROGRAM_ID);
+    let (contributor_pda, bump) = (0..canonical_bump).rev()
+        .find_map(|bump| {
+            SolanaPubkey::create_program_address(&[b"contributor", fundraiser.as_ref(), contributor.as_ref(), &[bump]], &PROGRAM_ID)
+                .ok()
+                .map(|pda| (pda, bump))
+        })
+        .expect("No other bump for the contributor PDA");
+
+    instruction.accounts[CONTRIBUTOR_ACCOUNT].pubkey = contributor_pda;
+    accounts[CONTRIBUTOR_ACCOUNT].0 = contributor_pda;
+    instruction.data[9..17].copy_from_slice(&(bump as u64).to_le_bytes());
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidPda));
+}
+
+#[test]
+fn later_contribution_adds_up(){
+    let mollusk = setup_mollusk();
+
+    let (result, _, contributor_pda) = process_contribute(&mollusk, 1_000, 0, 0, Some(2_000));
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let contributor_account = result.get_account(&contributor_pda).expect("Failed to get contributor account");
+    assert_eq!(from_bytes::<Contributor>(contributor_account.data()).amount, 3_000);
+}
+
+#[test]
+fn contribute_before_deadline(){
+    let mut mollusk = setup_mollusk();
+
+    // the last second of the fundraiser
+    mollusk.sysvars.clock.unix_timestamp = 1_000 + DURATION - 1;
+
+    let (result, _, _) = process_contribute(&mollusk, 1_000, 0, 1_000, Some(0));
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+}
+
+#[test]
+fn contribute_at_deadline(){
+    let mut mollusk = setup_mollusk();
+
+    // from the deadline on only refunds are possible
+    mollusk.sysvars.clock.unix_timestamp = 1_000 + DURATION;
+
+    let (result, _, _) = process_contribute(&mollusk, 1_000, 0, 1_000, Some(0));
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::DeadlineReached));
+}
+
+#[test]
+fn contribute_after_deadline(){
+    let mut mollusk = setup_mollusk();
+
+    mollusk.sysvars.clock.unix_timestamp = 1_000 + DURATION + 1;
+
+    let (result, _, _) = process_contribute(&mollusk, 1_000, 0, 1_000, Some(0));
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::DeadlineReached));
+}
+
+#[test]
+fn contribute_below_minimum(){
+    let mollusk = setup_mollusk();
+
+    let (result, _, _) = process_contribute(&mollusk, 2, 0, 0, Some(0));
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::BelowMinimum));
+}
+
+#[test]
+fn contribute_over_goal(){
+    let mollusk = setup_mollusk();
+
+    let (result, _, _) = process_contribute(&mollusk, 1_000, AMOUNT_TO_RAISE - 999, 0, Some(0));
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::GoalExceeded));
+}
+
+#[test]
+fn contribute_over_cap(){
+    let mollusk = setup_mollusk();
+
+    // a contributor can give at most 10% of the goal
+    let (result, _, _) = process_contribute(&mollusk, 1_000, 0, 0, Some(AMOUNT_TO_RAISE / 10 - 999));
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::ContributionCapExceeded));
+}
+
+#[test]
+fn contribute_below_campaign_minimum(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(0));
+    set_contribution_limits(&mut accounts, 1_001, 1_000, 0);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::BelowMinimum));
+}
+
+#[test]
+fn contribute_up_to_campaign_share(){
+    let mollusk = setup_mollusk();
+
+    // 25% of the goal, well above the default 10%
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(AMOUNT_TO_RAISE / 4 - 1_000));
+    set_contribution_limits(&mut accounts, 3, 2_500, 0);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(AMOUNT_TO_RAISE / 4 - 999));
+    set_contribution_limits(&mut accounts, 3, 2_500, 0);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::ContributionCapExceeded));
+}
+
+#[test]
+fn contribute_up_to_absolute_cap(){
+    let mollusk = setup_mollusk();
+
+    // the absolute cap is below the 10% share of the goal
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(1_000));
+    set_contribution_limits(&mut accounts, 3, 1_000, 2_000);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(1_001));
+    set_contribution_limits(&mut accounts, 3, 1_000, 2_000);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::ContributionCapExceeded));
+}
+
+#[test]
+fn contribute_to_another_campaign(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(0));
+
+    // the fundraiser data claims a campaign id its address wasn't derived from
+    let mut fundraiser_data = pod_read_unaligned::<Fundraiser>(accounts[FUNDRAISER].1.data());
+    fundraiser_data.campaign_id = CAMPAIGN_ID + 1;
+    accounts[FUNDRAISER].1.set_data_from_slice(bytes_of(&fundraiser_data));
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidPda));
+}
+
+#[test]
+fn contribute_to_vault_of_another_mint(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(0));
+
+    // a token account at the vault address for another mint
+    let vault = accounts[VAULT].0;
+    accounts[VAULT].1 = create_initialized_vault_account(vault, SolanaPubkey::new_unique(), &mollusk, 0);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidMint));
+}
+
+#[test]
+fn contribute_after_cancel(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, 0, 0, Some(0));
+
+    set_fundraiser_status(&mut accounts[FUNDRAISER].1, FundraiserStatus::Cancelled);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
+
+#[test]
+fn contribution_reaching_goal(){
+    let mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_contribute(&mollusk, 1_000, AMOUNT_TO_RAISE - 1_000, 0, Some(0));
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let fundraiser = result.get_account(&accounts[FUNDRAISER].0).expect("Failed to get fundraiser account");
+    assert_eq!(from_bytes::<Fundraiser>(fundraiser.data()).status, FundraiserStatus::GoalReached as u8);
+}
+
+#[test]
+fn contribute_after_goal_reached(){
+    let mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_contribute(&mollusk, 1_000, AMOUNT_TO_RAISE, 0, Some(0));
+
+    set_fundraiser_status(&mut accounts[FUNDRAISER].1, FundraiserStatus::GoalReached);
 
-}
\ No newline at end of file
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidStatus));
+}
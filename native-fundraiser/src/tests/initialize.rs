@@ -1,40 +1,90 @@
 use mollusk_svm::{
     program,
-    result::ProgramResult,
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
 };
 use solana_sdk::{
-    account::AccountSharedData, 
+    account::{AccountSharedData, ReadableAccount}, 
     instruction::{AccountMeta, Instruction}, 
+    program_pack::Pack, 
     pubkey::Pubkey as SolanaPubkey
 };
 
+use crate::constants::{MAX_CONTRIBUTION_BPS, MIN_AMOUNT_TO_RAISE};
+use crate::error::FundraiserError;
+use crate::state::{Fundraiser, FundraiserStatus};
+
 use crate::tests::{
     setup_mollusk,
     create_mint_account,
+    fundraiser_error,
+    CAMPAIGN_ID,
     PROGRAM_ID
 };
 
+/**
+ * Initializes a fundraiser for 1_000_000 over 30 seconds
+ */
+fn process_initialize(
+    mollusk: &Mollusk,
+    maker: SolanaPubkey,
+    campaign_id: u64,
+    min_contribution: u64,
+    max_contribution_bps: u64,
+    max_contribution: u64,
+) -> (InstructionResult, SolanaPubkey, SolanaPubkey) {
+    process_initialize_campaign(mollusk, maker, campaign_id, min_contribution, max_contribution_bps, max_contribution, 1_000_000, 30)
+}
 
-#[test]
-fn initialize(){
-
-    let mollusk = setup_mollusk();
-
-    let maker = SolanaPubkey::new_unique();
-    let (fundraiser, bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref()], &PROGRAM_ID);
+/**
+ * Initializes a fundraiser for the given goal over the given duration
+ * 
+ * @param mollusk: The mollusk instance
+ * @param maker: The maker of the fundraiser
+ * @param campaign_id: The id of the campaign among the maker's campaigns
+ * @param min_contribution: The smallest accepted contribution, 0 for the default
+ * @param max_contribution_bps: The per-wallet cap in basis points of the goal, 0 for the default
+ * @param max_contribution: The absolute per-wallet cap, 0 for none
+ * @param amount: The amount to raise
+ * @param duration: The timeframe of the fundraiser in seconds
+ * @returns: The result of the initialize instruction, the fundraiser PDA and the vault PDA
+ */
+#[allow(clippy::too_many_arguments)]
+fn process_initialize_campaign(
+    mollusk: &Mollusk,
+    maker: SolanaPubkey,
+    campaign_id: u64,
+    min_contribution: u64,
+    max_contribution_bps: u64,
+    max_contribution: u64,
+    amount: u64,
+    duration: i64,
+) -> (InstructionResult, SolanaPubkey, SolanaPubkey) {
+    let (fundraiser, bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref(), &campaign_id.to_le_bytes()], &PROGRAM_ID);
+    let (vault, vault_bump) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
 
     let mint_to_raise = SolanaPubkey::new_unique();
-    let mint_account = create_mint_account(&mollusk, &mint_to_raise);
+    let mint_account = create_mint_account(mollusk, &mint_to_raise);
     
     let (system_program, system_program_account) = program::keyed_account_for_system_program();
+    let (token_program, token_program_account) = (
+        spl_token::ID,
+        program::create_program_account_loader_v3(&spl_token::ID),
+    );
     
     let bump_u64 = bump as u64;
+    let vault_bump_u64 = vault_bump as u64;
 
     let data = [
         vec![0],
-        1_000_000u64.to_le_bytes().to_vec(), // amount
-        30u64.to_le_bytes().to_vec(), // duration
+        amount.to_le_bytes().to_vec(), // amount
+        duration.to_le_bytes().to_vec(), // duration in seconds
         bump_u64.to_le_bytes().to_vec(), // fundraiser_bump
+        min_contribution.to_le_bytes().to_vec(),
+        max_contribution_bps.to_le_bytes().to_vec(),
+        max_contribution.to_le_bytes().to_vec(),
+        campaign_id.to_le_bytes().to_vec(),
+        vault_bump_u64.to_le_bytes().to_vec(), // vault_bump
     ]
     .concat();
 
@@ -45,11 +95,13 @@ fn initialize(){
             AccountMeta::new(maker, true),
             AccountMeta::new(mint_to_raise, false),
             AccountMeta::new(fundraiser, false),
+            AccountMeta::new(vault, false),
             AccountMeta::new(system_program, false),
+            AccountMeta::new(token_program, false),
         ],
     );
 
-    let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
+    let result = mollusk.process_instruction(
         &instruction,
         &vec![
             (
@@ -58,9 +110,135 @@ fn initialize(){
             ),
             (mint_to_raise, mint_account),
             (fundraiser, AccountSharedData::new(0, 0, &SolanaPubkey::default())),
+            (vault, AccountSharedData::new(0, 0, &SolanaPubkey::default())),
             (system_program, system_program_account),
+            (token_program, token_program_account),
         ],
     );
 
-    assert!(matches!(result.program_result, ProgramResult::Success))
-}
\ No newline at end of file
+    (result, fundraiser, vault)
+}
+
+#[test]
+fn initialize(){
+
+    let mut mollusk = setup_mollusk();
+
+    // the fundraiser starts at the current time
+    mollusk.sysvars.clock.unix_timestamp = 1_700_000_000;
+
+    let (result, fundraiser, _) = process_initialize(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 0, 0);
+    assert!(matches!(result.program_result, ProgramResult::Success));
+
+    let fundraiser = result.get_account(&fundraiser).expect("Failed to get fundraiser account");
+    let fundraiser_data = bytemuck::from_bytes::<Fundraiser>(fundraiser.data());
+
+    assert_eq!(fundraiser_data.time_started, 1_700_000_000);
+    assert_eq!(fundraiser_data.duration, 30);
+    assert_eq!(fundraiser_data.campaign_id, CAMPAIGN_ID);
+    assert_eq!(fundraiser_data.status, FundraiserStatus::Active as u8);
+
+    // without limits of its own the campaign gets the defaults
+    assert_eq!(fundraiser_data.min_contribution, MIN_AMOUNT_TO_RAISE);
+    assert_eq!(fundraiser_data.max_contribution_bps, MAX_CONTRIBUTION_BPS);
+    assert_eq!(fundraiser_data.max_contribution, 0);
+}
+
+#[test]
+fn initialize_with_contribution_limits(){
+    let mollusk = setup_mollusk();
+
+    let (result, fundraiser, _) = process_initialize(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 1_000, 2_500, 50_000);
+    assert!(matches!(result.program_result, ProgramResult::Success));
+
+    let fundraiser = result.get_account(&fundraiser).expect("Failed to get fundraiser account");
+    let fundraiser_data = bytemuck::from_bytes::<Fundraiser>(fundraiser.data());
+
+    assert_eq!(fundraiser_data.min_contribution, 1_000);
+    assert_eq!(fundraiser_data.max_contribution_bps, 2_500);
+    assert_eq!(fundraiser_data.max_contribution, 50_000);
+}
+
+#[test]
+fn initialize_with_invalid_contribution_limits(){
+    let mollusk = setup_mollusk();
+
+    // more than the whole goal per contributor
+    let (result, _, _) = process_initialize(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 10_001, 0);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidContributionLimits));
+
+    // a cap below the minimum
+    let (result, _, _) = process_initialize(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 1_000, 0, 999);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidContributionLimits));
+}
+
+#[test]
+fn initialize_with_invalid_duration(){
+    let mollusk = setup_mollusk();
+
+    // a campaign that ends as it starts
+    let (result, _, _) = process_initialize_campaign(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 0, 0, 1_000_000, 0);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidDuration));
+
+    // a campaign that ended before it started
+    let (result, _, _) = process_initialize_campaign(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 0, 0, 1_000_000, -30);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidDuration));
+}
+
+#[test]
+fn initialize_with_cap_below_minimum(){
+    let mollusk = setup_mollusk();
+
+    // with the defaults a goal of 20 caps every contributor at 2, below the minimum of 3
+    let (result, _, _) = process_initialize_campaign(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 0, 0, 20, 30);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidContributionLimits));
+
+    // a goal of 30 leaves exactly the minimum
+    let (result, _, _) = process_initialize_campaign(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 0, 0, 30, 30);
+    assert!(matches!(result.program_result, ProgramResult::Success));
+}
+
+#[test]
+fn initialize_with_nothing_to_raise(){
+    let mollusk = setup_mollusk();
+
+    let (result, _, _) = process_initialize_campaign(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 0, 0, 0, 30);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidGoal));
+}
+
+#[test]
+fn initialize_several_campaigns(){
+    let mollusk = setup_mollusk();
+    let maker = SolanaPubkey::new_unique();
+
+    // every campaign of the maker lives at its own PDA
+    let (result, first, _) = process_initialize(&mollusk, maker, 1, 0, 0, 0);
+    assert!(matches!(result.program_result, ProgramResult::Success));
+    let (result, second, _) = process_initialize(&mollusk, maker, 2, 0, 0, 0);
+    assert!(matches!(result.program_result, ProgramResult::Success));
+
+    assert_ne!(first, second);
+    let second = result.get_account(&second).expect("Failed to get fundraiser account");
+    assert_eq!(bytemuck::from_bytes::<Fundraiser>(second.data()).campaign_id, 2);
+}
+
+#[test]
+fn initialize_creates_vault(){
+    let mollusk = setup_mollusk();
+
+    let (result, fundraiser, vault) = process_initialize(&mollusk, SolanaPubkey::new_unique(), CAMPAIGN_ID, 0, 0, 0);
+    assert!(matches!(result.program_result, ProgramResult::Success));
+
+    let (_, vault_bump) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
+    let fundraiser = result.get_account(&fundraiser).expect("Failed to get fundraiser account");
+    let fundraiser_data = bytemuck::from_bytes::<Fundraiser>(fundraiser.data());
+    assert_eq!(fundraiser_data.vault_bump, vault_bump as u64);
+
+    // the vault holds mint_to_raise and is its own authority
+    let vault_account = result.get_account(&vault).expect("Failed to get vault account");
+    assert_eq!(vault_account.owner(), &spl_token::ID);
+    let vault_data = spl_token::state::Account::unpack(vault_account.data()).unwrap();
+    assert_eq!(vault_data.mint.to_bytes(), fundraiser_data.mint_to_raise);
+    assert_eq!(vault_data.owner, vault);
+    assert_eq!(vault_data.amount, 0);
+}
@@ -3,23 +3,28 @@ mod initialize;
 mod contribute;
 mod checker;
 mod refund;
+mod cancel;
 
 /**
  * Setup functions for tests
  */
 use mollusk_svm::{
     program,
+    result::ProgramResult,
     Mollusk,
 };
 use solana_sdk::{
-    account::{AccountSharedData, WritableAccount}, 
+    account::{AccountSharedData, ReadableAccount, WritableAccount}, 
+    program_error::ProgramError,
     program_option::COption, 
     program_pack::Pack, 
     pubkey::Pubkey as SolanaPubkey
 };
 use spl_token::state::AccountState;
 use bytemuck::bytes_of;
-use crate::state::Fundraiser;
+use crate::constants::{MAX_CONTRIBUTION_BPS, MIN_AMOUNT_TO_RAISE};
+use crate::error::FundraiserError;
+use crate::state::{Contributor, Fundraiser, FundraiserStatus};
 
 
 const PROGRAM_ID: SolanaPubkey = SolanaPubkey::new_from_array(five8_const::decode_32_const(
@@ -28,6 +33,7 @@ const PROGRAM_ID: SolanaPubkey = SolanaPubkey::new_from_array(five8_const::decod
 
 const AMOUNT_TO_RAISE: u64 = 1_000_000;
 const DURATION: i64 = 30;
+const CAMPAIGN_ID: u64 = 7;
 
 fn setup_mollusk() -> Mollusk{
     let mut mollusk = Mollusk::new(&PROGRAM_ID, "target/deploy/native_fundraiser");
@@ -42,7 +48,17 @@ fn setup_mollusk() -> Mollusk{
 }
 
 /**
- * Creates an initialized fundraiser account
+ * The result of an instruction that failed with a fundraiser error
+ * 
+ * @param error: The expected error
+ * @returns: The program result the instruction should end with
+ */
+fn fundraiser_error(error: FundraiserError) -> ProgramResult {
+    ProgramResult::Failure(ProgramError::Custom(error as u32))
+}
+
+/**
+ * Creates an initialized fundraiser account for campaign CAMPAIGN_ID with the default contribution limits
  * 
  * @param maker: The maker of the fundraiser
  * @param mollusk: The mollusk instance
@@ -61,6 +77,13 @@ fn create_initialized_fundraiser_account(
     fundraiser_bump: u64,
 ) -> AccountSharedData {
 
+    // the vault bump follows from the fundraiser address
+    let fundraiser = SolanaPubkey::create_program_address(
+        &[b"fundraiser", maker.as_ref(), &CAMPAIGN_ID.to_le_bytes(), &[fundraiser_bump as u8]],
+        &PROGRAM_ID,
+    ).unwrap();
+    let (_, vault_bump) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
+
     let mut fundraiser_account = AccountSharedData::new(
         mollusk.sysvars.rent.minimum_balance(Fundraiser::LEN),
         Fundraiser::LEN,
@@ -74,11 +97,57 @@ fn create_initialized_fundraiser_account(
         time_started,
         duration: DURATION,
         bump: fundraiser_bump,
+        campaign_id: CAMPAIGN_ID,
+        vault_bump: vault_bump as u64,
+        min_contribution: MIN_AMOUNT_TO_RAISE,
+        max_contribution_bps: MAX_CONTRIBUTION_BPS,
+        max_contribution: 0,
+        status: FundraiserStatus::Active as u8,
+        _padding: [0; 7],
     }));
 
     fundraiser_account
 }
 
+/**
+ * Moves an initialized fundraiser account to another status
+ * 
+ * @param fundraiser_account: The fundraiser account
+ * @param status: The new status of the fundraiser
+ */
+fn set_fundraiser_status(fundraiser_account: &mut AccountSharedData, status: FundraiserStatus) {
+    let mut fundraiser_data = bytemuck::pod_read_unaligned::<Fundraiser>(fundraiser_account.data());
+    fundraiser_data.status = status as u8;
+    fundraiser_account.set_data_from_slice(bytes_of(&fundraiser_data));
+}
+
+/**
+ * Creates an initialized contributor account
+ * 
+ * @param mollusk: The mollusk instance
+ * @param amount: The amount the contributor has contributed so far
+ * @param contributor_bump: The bump of the contributor PDA
+ * @returns: An initialized contributor account
+ */
+fn create_initialized_contributor_account(
+    mollusk: &Mollusk,
+    amount: u64,
+    contributor_bump: u64,
+) -> AccountSharedData {
+
+    let mut contributor_account = AccountSharedData::new(
+        mollusk.sysvars.rent.minimum_balance(Contributor::LEN),
+        Contributor::LEN,
+        &PROGRAM_ID,
+    );
+    contributor_account.set_data_from_slice(bytes_of::<Contributor>(&Contributor {
+        amount,
+        contributor_bump,
+    }));
+
+    contributor_account
+}
+
 /**
  * Creates an initialized ata account
  * 
@@ -122,16 +191,16 @@ fn create_initialized_ata_account(
 
 
 /**
- * Creates an initialized vault account
+ * Creates an initialized vault account, like initialize the vault is its own authority
  * 
- * @param fundraiser: The fundraiser pda account that holds the fundraiser state
+ * @param vault: The vault pda
  * @param mint_to_raise: The mint that the vault is for
  * @param mollusk: The mollusk instance
  * @param amount: The amount of the vault
  * @returns: An initialized vault account
  */
 fn create_initialized_vault_account(
-    fundraiser: SolanaPubkey,
+    vault: SolanaPubkey,
     mint_to_raise: SolanaPubkey,
     mollusk: &Mollusk,
     amount: u64,
@@ -149,7 +218,7 @@ fn create_initialized_vault_account(
     spl_token::state::Account::pack(
         spl_token::state::Account {
             mint: mint_to_raise,
-            owner: fundraiser,
+            owner: vault,
             amount: amount,
             delegate: COption::None,
             state: AccountState::Initialized,
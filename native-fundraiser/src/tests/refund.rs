@@ -1,97 +1,281 @@
 use mollusk_svm::{
     program,
     result::ProgramResult,
+    Mollusk,
 };
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount}, 
     instruction::{AccountMeta, Instruction}, 
+    program_error::ProgramError,
     program_pack::Pack, 
     pubkey::Pubkey as SolanaPubkey
 };
-use bytemuck::bytes_of;
+use bytemuck::from_bytes;
 
-use crate::state::Contributor;
+use crate::error::FundraiserError;
+use crate::state::{Fundraiser, FundraiserStatus};
 
 use crate::tests::{
     setup_mollusk,
     create_initialized_fundraiser_account,
+    create_initialized_contributor_account,
     create_initialized_ata_account,
     create_initialized_vault_account,
+    fundraiser_error,
+    set_fundraiser_status,
+    AMOUNT_TO_RAISE,
+    CAMPAIGN_ID,
+    DURATION,
     PROGRAM_ID
 };
 
-#[test]
-fn refund(){
-    let mut mollusk = setup_mollusk();
+// account positions in the refund instruction
+const CONTRIBUTOR: usize = 0;
+const FUNDRAISER: usize = 1;
+const CONTRIBUTOR_ATA: usize = 2;
+const CONTRIBUTOR_ACCOUNT: usize = 3;
+const VAULT: usize = 4;
+
+/**
+ * Sets up a refund of a 100_000 contribution to a fundraiser that started at 0
+ * 
+ * @param mollusk: The mollusk instance
+ * @param current_amount: The amount raised by the fundraiser
+ * @returns: The refund instruction and its accounts
+ */
+fn setup_refund(mollusk: &Mollusk, current_amount: u64) -> (Instruction, Vec<(SolanaPubkey, AccountSharedData)>) {
     let contributor = SolanaPubkey::new_unique(); // The one who contributed to the fundraiser
     let maker = SolanaPubkey::new_unique(); // The one who created the fundraiser
     let mint_to_raise = SolanaPubkey::new_unique(); // Currency in which was raised
-    let (fundraiser, fundraiser_bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref()], &PROGRAM_ID);
-    let fundraiser_account = create_initialized_fundraiser_account(maker, &mollusk, mint_to_raise, 100_000, 0, fundraiser_bump as u64);
+    let (fundraiser, fundraiser_bump) = SolanaPubkey::find_program_address(&[b"fundraiser", maker.as_ref(), &CAMPAIGN_ID.to_le_bytes()], &PROGRAM_ID);
+    let fundraiser_account = create_initialized_fundraiser_account(maker, mollusk, mint_to_raise, current_amount, 0, fundraiser_bump as u64);
 
     let (token_program, token_program_account) = (
         spl_token::ID,
         program::create_program_account_loader_v3(&spl_token::ID),
     );
-    let (vault, vault_bump) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
-    let (contributor_account, contributer_account_bump) = SolanaPubkey::find_program_address(&[b"contributor", fundraiser.as_ref(), contributor.as_ref()], &PROGRAM_ID);
+    let (vault, _) = SolanaPubkey::find_program_address(&[b"vault", fundraiser.as_ref()], &PROGRAM_ID);
+    let (contributor_account, contributor_account_bump) = SolanaPubkey::find_program_address(&[b"contributor", fundraiser.as_ref(), contributor.as_ref()], &PROGRAM_ID);
     let contributor_ata = SolanaPubkey::new_unique();
 
-    let vault_account = create_initialized_vault_account(vault, mint_to_raise, &mollusk, 100_000);
-
-    let mut contributor_account_data = AccountSharedData::new(
-        mollusk.sysvars.rent.minimum_balance(Contributor::LEN),
-        Contributor::LEN,
-        &PROGRAM_ID,
-    );
-    contributor_account_data.set_data_from_slice(bytes_of::<Contributor>(&Contributor {
-        amount: 100_000,
-        contributor_bump: contributer_account_bump as u64
-    }));
-
-    let contributor_ata_account = create_initialized_ata_account(contributor, &mollusk, mint_to_raise, 123);
-
-    // Mock the time to be past the duration
-    mollusk.sysvars.clock.unix_timestamp = 31;
-
-    let fundraiser_bump_u64 = fundraiser_bump as u64;
-    let vault_bump_u64 = vault_bump as u64;
-    let data = [
-        vec![3],
-        fundraiser_bump_u64.to_le_bytes().to_vec(),
-        vault_bump_u64.to_le_bytes().to_vec(),
-    ]
-    .concat();
+    let vault_account = create_initialized_vault_account(vault, mint_to_raise, mollusk, current_amount);
+    let contributor_account_data = create_initialized_contributor_account(mollusk, 100_000, contributor_account_bump as u64);
+    let contributor_ata_account = create_initialized_ata_account(contributor, mollusk, mint_to_raise, 123);
 
     let instruction = Instruction::new_with_bytes(
         PROGRAM_ID,
-        &data,
+        &[3],
         vec![
             AccountMeta::new(contributor, true),
             AccountMeta::new(fundraiser, false),
             AccountMeta::new(contributor_ata, false),
             AccountMeta::new(contributor_account, false),
-            AccountMeta::new(vault, true),
+            AccountMeta::new(vault, false),
             AccountMeta::new(token_program, false),
         ],
     );
 
-    let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(
-        &instruction,
-        &vec![
-            (contributor, AccountSharedData::new(1_000_000_000, 0, &SolanaPubkey::default())),
-            (fundraiser, fundraiser_account),
-            (contributor_ata, contributor_ata_account),
-            (contributor_account, contributor_account_data),
-            (vault, vault_account),
-            (token_program, token_program_account),
-        ],
-    );
+    let accounts = vec![
+        (contributor, AccountSharedData::new(1_000_000_000, 0, &SolanaPubkey::default())),
+        (fundraiser, fundraiser_account),
+        (contributor_ata, contributor_ata_account),
+        (contributor_account, contributor_account_data),
+        (vault, vault_account),
+        (token_program, token_program_account),
+    ];
+
+    (instruction, accounts)
+}
+
+#[test]
+fn refund(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_refund(&mollusk, 100_000);
+
+    // Mock the time to be past the duration
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    let result: mollusk_svm::result::InstructionResult = mollusk.process_instruction(&instruction, &accounts);
     assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
 
-    let vault = result.get_account(&vault).expect("Failed to get vault account");
+    let vault = result.get_account(&instruction.accounts[VAULT].pubkey).expect("Failed to get vault account");
     let vault_data = vault.data();
     let vault_account_info = spl_token::state::Account::unpack(&vault_data).unwrap();
 
     assert_eq!(vault_account_info.amount, 0);
+
+    let contributor_ata = result.get_account(&instruction.accounts[CONTRIBUTOR_ATA].pubkey).expect("Failed to get contributor ata");
+    assert_eq!(spl_token::state::Account::unpack(contributor_ata.data()).unwrap().amount, 100_123);
+
+    // the fundraiser failed at the deadline, the refund writes it back
+    let fundraiser = result.get_account(&instruction.accounts[FUNDRAISER].pubkey).expect("Failed to get fundraiser account");
+    assert_eq!(from_bytes::<Fundraiser>(fundraiser.data()).current_amount, 0);
+    assert_eq!(from_bytes::<Fundraiser>(fundraiser.data()).status, FundraiserStatus::Failed as u8);
+
+    // the contributor account is closed and its rent is back with the contributor
+    let contributor_account = result.get_account(&instruction.accounts[CONTRIBUTOR_ACCOUNT].pubkey).expect("Failed to get contributor account");
+    assert_eq!(contributor_account.lamports(), 0);
+    let contributor = result.get_account(&instruction.accounts[CONTRIBUTOR].pubkey).expect("Failed to get contributor");
+    assert_eq!(contributor.lamports(), 1_000_000_000 + accounts[CONTRIBUTOR_ACCOUNT].1.lamports());
+}
+
+#[test]
+fn refund_of_more_than_raised(){
+    let mut mollusk = setup_mollusk();
+
+    // the contributor account records 100_000, the fundraiser only 50_000
+    let (instruction, accounts) = setup_refund(&mollusk, 50_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidContributorAmount));
+}
+
+#[test]
+fn refund_requires_contributor_signature(){
+    let mut mollusk = setup_mollusk();
+    let (mut instruction, accounts) = setup_refund(&mollusk, 100_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    instruction.accounts[CONTRIBUTOR].is_signer = false;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::MissingRequiredSignature));
+}
+
+#[test]
+fn refund_before_deadline(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_refund(&mollusk, 100_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION - 1;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::DeadlineNotReached));
+}
+
+#[test]
+fn refund_after_goal_reached(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_refund(&mollusk, AMOUNT_TO_RAISE);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::GoalReached));
+}
+
+#[test]
+fn refund_from_wrong_vault(){
+    let mut mollusk = setup_mollusk();
+    let (mut instruction, mut accounts) = setup_refund(&mollusk, 100_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    // a token account the vault PDA doesn't live at
+    let fake_vault = SolanaPubkey::new_unique();
+    let mint_to_raise = spl_token::state::Account::unpack(accounts[VAULT].1.data()).unwrap().mint;
+    instruction.accounts[VAULT].pubkey = fake_vault;
+    accounts[VAULT] = (fake_vault, create_initialized_vault_account(fake_vault, mint_to_raise, &mollusk, 100_000));
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidPda));
+}
+
+#[test]
+fn refund_of_another_contributor(){
+    let mut mollusk = setup_mollusk();
+    let (mut instruction, mut accounts) = setup_refund(&mollusk, 100_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    // someone else signs with the contributor's account
+    let other = SolanaPubkey::new_unique();
+    instruction.accounts[CONTRIBUTOR].pubkey = other;
+    accounts[CONTRIBUTOR].0 = other;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::InvalidPda));
+}
+
+#[test]
+fn refund_twice(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, accounts) = setup_refund(&mollusk, 200_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    // the vault still holds another contribution, but the closed contributor account has no data left
+    let result = mollusk.process_instruction(&instruction, &result.resulting_accounts);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn refund_after_cancel_before_deadline(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_refund(&mollusk, 100_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION - 1;
+
+    // a cancelled fundraiser doesn't make its contributors wait for the deadline
+    set_fundraiser_status(&mut accounts[FUNDRAISER].1, FundraiserStatus::Cancelled);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let contributor_ata = result.get_account(&instruction.accounts[CONTRIBUTOR_ATA].pubkey).expect("Failed to get contributor ata");
+    assert_eq!(spl_token::state::Account::unpack(contributor_ata.data()).unwrap().amount, 100_123);
+}
+
+#[test]
+fn refund_after_cancel_with_goal_reached(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_refund(&mollusk, AMOUNT_TO_RAISE);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    set_fundraiser_status(&mut accounts[FUNDRAISER].1, FundraiserStatus::Cancelled);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let fundraiser = result.get_account(&instruction.accounts[FUNDRAISER].pubkey).expect("Failed to get fundraiser account");
+    assert_eq!(from_bytes::<Fundraiser>(fundraiser.data()).current_amount, AMOUNT_TO_RAISE - 100_000);
+}
+
+#[test]
+fn refund_after_failed(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_refund(&mollusk, 300_000);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    // an earlier refund already wrote the failure back
+    set_fundraiser_status(&mut accounts[FUNDRAISER].1, FundraiserStatus::Failed);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(matches!(result.program_result, ProgramResult::Success), "Processing instruction failed");
+
+    let fundraiser = result.get_account(&instruction.accounts[FUNDRAISER].pubkey).expect("Failed to get fundraiser account");
+    assert_eq!(from_bytes::<Fundraiser>(fundraiser.data()).status, FundraiserStatus::Failed as u8);
+}
+
+#[test]
+fn refund_with_status_goal_reached(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_refund(&mollusk, AMOUNT_TO_RAISE);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    set_fundraiser_status(&mut accounts[FUNDRAISER].1, FundraiserStatus::GoalReached);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, fundraiser_error(FundraiserError::GoalReached));
+}
+
+#[test]
+fn refund_after_withdrawn(){
+    let mut mollusk = setup_mollusk();
+    let (instruction, mut accounts) = setup_refund(&mollusk, AMOUNT_TO_RAISE);
+    mollusk.sysvars.clock.unix_timestamp = DURATION + 1;
+
+    // the maker was paid out and checker closed the fundraiser, nothing is left to refund
+    accounts[FUNDRAISER].1 = AccountSharedData::new(0, 0, &PROGRAM_ID);
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_eq!(result.program_result, ProgramResult::Failure(ProgramError::InvalidAccountData));
 }
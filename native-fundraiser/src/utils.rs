@@ -1,7 +1,11 @@
 use pinocchio::{
+    account_info::AccountInfo,
     pubkey,
-    pubkey::Pubkey
+    pubkey::Pubkey,
+    ProgramResult,
 };
+use pinocchio_token::state::TokenAccount;
+use crate::error::FundraiserError;
 
 
 #[inline]
@@ -9,8 +13,42 @@ pub fn validate_pda(
     seeds: &[&[u8]],
     program_id: &Pubkey,
     address: &Pubkey,
-) {
+) -> ProgramResult {
 
-    let pda = pubkey::create_program_address(seeds, program_id).unwrap();
-    assert!(address.eq(&pda))
+    let pda = pubkey::create_program_address(seeds, program_id).map_err(|_| FundraiserError::InvalidPda)?;
+    if address.ne(&pda) {
+        return Err(FundraiserError::InvalidPda.into());
+    }
+
+    Ok(())
+}
+
+// the token account at the vault address has to be for the mint the fundraiser raises
+#[inline]
+pub fn validate_vault_mint(
+    vault: &AccountInfo,
+    mint_to_raise: &Pubkey,
+) -> ProgramResult {
+
+    if TokenAccount::from_account_info(vault)?.mint() != mint_to_raise {
+        return Err(FundraiserError::InvalidMint.into());
+    }
+
+    Ok(())
+}
+
+// empties a program owned account and moves its rent to `destination`,
+// without data it can't be read as its old state again in the same transaction
+#[inline]
+pub fn close_program_account(
+    account: &AccountInfo,
+    destination: &AccountInfo,
+) -> ProgramResult {
+
+    account.try_borrow_mut_data()?.fill(0);
+    account.realloc(0, false)?;
+    *destination.try_borrow_mut_lamports()? += *account.try_borrow_lamports()?;
+    *account.try_borrow_mut_lamports()? = 0;
+
+    Ok(())
 }